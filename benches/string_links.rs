@@ -0,0 +1,165 @@
+//! Throughput of the STRING links parsers and of the vocabulary lookups on a
+//! synthetic sample of `protein.links.full.v12.0.txt`.
+//!
+//! Run it with `cargo bench --bench string_links`. The sample has
+//! `SPW_BENCH_LINKS` links, one million by default, and every benchmark
+//! prints the best of [`RUNS`] runs in links and MB per second.
+
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use spw::names;
+use spw::node_type::NodeType;
+use spw::readers::{par_for_each, Record, RecordRef, Records, StringLink, STRING_LINK_CHANNELS};
+use spw::vocab::{IdPolicy, Vocab, VocabBuilder};
+use std::fs;
+use std::hint::black_box;
+use std::io::prelude::*;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How many times each benchmark is run.
+const RUNS: usize = 5;
+
+/// The name of the synthetic protein with the given index.
+fn protein(index: usize) -> String {
+    format!("{}.PROT{:08}", 9606 + index % 16, index)
+}
+
+/// The lines of the synthetic links, without the header, with about ten
+/// links per protein and the sparse channel scores of the real file.
+fn synthetic_links(num_links: usize, num_proteins: usize) -> Vec<u8> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut data = Vec::new();
+    for _ in 0..num_links {
+        write!(
+            data,
+            "{} {}",
+            protein(rng.gen_range(0..num_proteins)),
+            protein(rng.gen_range(0..num_proteins))
+        )
+        .unwrap();
+        for _ in STRING_LINK_CHANNELS {
+            let score = if rng.gen_bool(0.2) {
+                rng.gen_range(1..1000)
+            } else {
+                0
+            };
+            write!(data, " {}", score).unwrap();
+        }
+        writeln!(data, " {}", rng.gen_range(150..1000)).unwrap();
+    }
+    data
+}
+
+/// Run `f` [`RUNS`] times and print the best throughput.
+fn bench(name: &str, num_links: usize, num_bytes: usize, mut f: impl FnMut() -> Result<()>) {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        f().unwrap();
+        best = best.min(start.elapsed());
+    }
+    let secs = best.as_secs_f64();
+    println!(
+        "{:<32} {:>10.3} Mlinks/s {:>10.1} MB/s",
+        name,
+        num_links as f64 / secs / 1e6,
+        num_bytes as f64 / secs / 1e6
+    );
+}
+
+/// The lines of `data`, without the newlines.
+fn lines(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    data.strip_suffix(b"\n")
+        .unwrap_or(data)
+        .split(|&byte| byte == b'\n')
+}
+
+fn main() -> Result<()> {
+    let num_links = std::env::var("SPW_BENCH_LINKS")
+        .ok()
+        .map(|num_links| num_links.parse())
+        .transpose()?
+        .unwrap_or(1_000_000);
+    let num_proteins = (num_links / 10).max(1000);
+    let dir = std::env::temp_dir().join(format!("spw-bench-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+
+    let data = synthetic_links(num_links, num_proteins);
+    let path = dir.join("protein.links.full.txt");
+    let mut file = fs::File::create(&path)?;
+    writeln!(
+        file,
+        "protein1 protein2 {} combined_score",
+        STRING_LINK_CHANNELS.join(" ")
+    )?;
+    file.write_all(&data)?;
+    drop(file);
+
+    let mut builder = VocabBuilder::new(&dir, 1 << 20, IdPolicy::default());
+    for index in 0..num_proteins {
+        builder.add(NodeType::Protein, protein(index))?;
+    }
+    builder.build(dir.join("vocab"))?;
+    let vocab = Vocab::load(dir.join("vocab"))?;
+
+    println!(
+        "{} synthetic links, {} proteins, {:.1} MB",
+        num_links,
+        num_proteins,
+        data.len() as f64 / 1e6
+    );
+    bench("parse", num_links, data.len(), || {
+        for line in lines(&data) {
+            black_box(StringLink::parse(std::str::from_utf8(line)?)?);
+        }
+        Ok(())
+    });
+    bench("parse_ref", num_links, data.len(), || {
+        for line in lines(&data) {
+            black_box(StringLink::parse_ref(line)?);
+        }
+        Ok(())
+    });
+    bench("parse_ref + id_of", num_links, data.len(), || {
+        for line in lines(&data) {
+            let link = StringLink::parse_ref(line)?;
+            black_box(vocab.id_of(&names::node_ref(link.protein1)));
+            black_box(vocab.id_of(&names::node_ref(link.protein2)));
+        }
+        Ok(())
+    });
+    bench("Records::try_for_each_ref", num_links, data.len(), || {
+        Records::<StringLink>::open(&path)?.try_for_each_ref(|link| {
+            black_box(link);
+            Ok(())
+        })
+    });
+    let num_threads = thread::available_parallelism()?.get();
+    bench(
+        &format!("par_for_each + id_of, {} threads", num_threads),
+        num_links,
+        data.len(),
+        || par_lookups(&path, &vocab, num_threads),
+    );
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+/// Parse the file with [`par_for_each`] and look up both proteins.
+fn par_lookups(path: &Path, vocab: &Vocab, num_threads: usize) -> Result<()> {
+    let mut found = vec![0usize; num_threads];
+    par_for_each::<StringLink, _, _>(path, &mut found, |found, link| {
+        for protein in [link.protein1, link.protein2] {
+            if vocab.id_of(&names::node_ref(protein)).is_some() {
+                *found += 1;
+            }
+        }
+        Ok(())
+    })?;
+    black_box(found);
+    Ok(())
+}
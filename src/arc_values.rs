@@ -0,0 +1,199 @@
+//! Lists of integers attached to the arcs of the graph, stored next to it.
+//!
+//! This is the format of the edge types of [`crate::labels`] and of the arc
+//! weights of [`crate::weights`]. A store with path `{path}` is made of:
+//! - `{path}`: for each node, its outdegree, then for each of its successors
+//!   in increasing order the gap from the previous one (the first one is
+//!   stored as is), the number of its values and the values, all as LEB128
+//!   varints;
+//! - `{path}.offsets`: the offset of each node in `{path}`, and its length,
+//!   as little-endian `u64`s.
+//!
+//! The files repeat the successors, so the values of an arc can be found
+//! without loading the graph.
+
+use crate::utils::{mmap, read_varint, write_varint};
+use anyhow::{bail, ensure, Result};
+use memmap2::Mmap;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+
+/// Writes the values of arcs given in increasing order of source and
+/// successor.
+pub struct ArcValuesWriter {
+    data: io::BufWriter<fs::File>,
+    offsets: io::BufWriter<fs::File>,
+    num_nodes: usize,
+    num_arcs: usize,
+    written: u64,
+    /// The next node whose arcs must be written
+    node: usize,
+    /// The successors of the arcs of `node`, with the ends of their values
+    arcs: Vec<(usize, usize)>,
+    values: Vec<usize>,
+    buffer: Vec<u8>,
+}
+
+impl ArcValuesWriter {
+    /// Create the files of a store with the given path.
+    pub fn new(path: &str, num_nodes: usize) -> Result<Self> {
+        Ok(Self {
+            data: io::BufWriter::new(fs::File::create(path)?),
+            offsets: io::BufWriter::new(fs::File::create(format!("{}.offsets", path))?),
+            num_nodes,
+            num_arcs: 0,
+            written: 0,
+            node: 0,
+            arcs: Vec::new(),
+            values: Vec::new(),
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Append values to the arc from `src` to `dst`, which is either the
+    /// last one or follows it.
+    pub fn push(&mut self, src: usize, dst: usize, values: &[usize]) -> Result<()> {
+        ensure!(
+            src >= self.node && src < self.num_nodes && dst < self.num_nodes,
+            "Arc {} -> {} out of order or out of range",
+            src,
+            dst
+        );
+        while self.node < src {
+            self.write_node()?;
+        }
+        match self.arcs.last_mut() {
+            Some((last, end)) if *last == dst => *end += values.len(),
+            Some((last, _)) if *last > dst => {
+                bail!("Arc {} -> {} out of order", src, dst)
+            }
+            _ => self.arcs.push((dst, self.values.len() + values.len())),
+        }
+        self.values.extend_from_slice(values);
+        Ok(())
+    }
+
+    /// Write the arcs of the current node and move to the next one.
+    fn write_node(&mut self) -> Result<()> {
+        self.buffer.clear();
+        write_varint(&mut self.buffer, self.arcs.len());
+        let mut previous = 0;
+        let mut start = 0;
+        for &(dst, end) in &self.arcs {
+            write_varint(&mut self.buffer, dst - previous);
+            write_varint(&mut self.buffer, end - start);
+            for &value in &self.values[start..end] {
+                write_varint(&mut self.buffer, value);
+            }
+            previous = dst;
+            start = end;
+        }
+        self.offsets.write_all(&self.written.to_le_bytes())?;
+        self.data.write_all(&self.buffer)?;
+        self.written += self.buffer.len() as u64;
+        self.num_arcs += self.arcs.len();
+        self.arcs.clear();
+        self.values.clear();
+        self.node += 1;
+        Ok(())
+    }
+
+    /// Write the remaining nodes, returning the number of arcs.
+    pub fn finish(mut self) -> Result<usize> {
+        while self.node < self.num_nodes {
+            self.write_node()?;
+        }
+        self.offsets.write_all(&self.written.to_le_bytes())?;
+        self.data.flush()?;
+        self.offsets.flush()?;
+        Ok(self.num_arcs)
+    }
+}
+
+/// Read-only, memory-mapped access to a store written by
+/// [`ArcValuesWriter`].
+pub struct ArcValues {
+    data: Mmap,
+    offsets: Mmap,
+    num_nodes: usize,
+}
+
+impl ArcValues {
+    /// Load the store with the given path and number of nodes.
+    pub fn load(path: &str, num_nodes: usize) -> Result<Self> {
+        let data = mmap(path)?;
+        let offsets = mmap(format!("{}.offsets", path))?;
+        ensure!(
+            offsets.len() == (num_nodes + 1) * 8,
+            "{}.offsets does not match the number of nodes",
+            path
+        );
+        Ok(Self {
+            data,
+            offsets,
+            num_nodes,
+        })
+    }
+
+    /// The successors of a node in increasing order, each with its values.
+    pub fn successors(&self, node: usize) -> Successors<'_> {
+        if node >= self.num_nodes {
+            return Successors {
+                data: &self.data,
+                pos: 0,
+                remaining: 0,
+                previous: 0,
+            };
+        }
+        let mut pos =
+            u64::from_le_bytes(self.offsets[node * 8..node * 8 + 8].try_into().unwrap()) as usize;
+        let remaining = read_varint(&self.data, &mut pos);
+        Successors {
+            data: &self.data,
+            pos,
+            remaining,
+            previous: 0,
+        }
+    }
+
+    /// The values of the arc from `src` to `dst`, if there is such an arc.
+    pub fn values(&self, src: usize, dst: usize) -> Option<Vec<usize>> {
+        self.successors(src)
+            .find(|(succ, _)| *succ >= dst)
+            .filter(|(succ, _)| *succ == dst)
+            .map(|(_, values)| values)
+    }
+}
+
+/// The iterator returned by [`ArcValues::successors`].
+pub struct Successors<'a> {
+    data: &'a [u8],
+    pos: usize,
+    remaining: usize,
+    previous: usize,
+}
+
+impl Iterator for Successors<'_> {
+    type Item = (usize, Vec<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let succ = self.previous + read_varint(self.data, &mut self.pos);
+        self.previous = succ;
+        let num_values = read_varint(self.data, &mut self.pos);
+        let values = (0..num_values)
+            .map(|_| read_varint(self.data, &mut self.pos))
+            .collect();
+        Some((succ, values))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Successors<'_> {}
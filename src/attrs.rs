@@ -0,0 +1,557 @@
+//! Typed attributes of the nodes, e.g. the preferred names of the STRING
+//! proteins or the labels of the ontology terms.
+//!
+//! A source with [`crate::sources::Source::attr_columns`] pushes the
+//! attributes of its nodes in any order. They are sorted on disk by
+//! [`AttrsBuilder`] and stored next to the graph, one file per column:
+//! - `{basename}.attrs.json`: the [`AttrsMetadata`], with the name and type
+//!   of each column;
+//! - `{basename}.attrs.{column}` for an [`AttrType::Int`] column: the value
+//!   of each node as a little-endian `u64`, [`MISSING_INT`] if it has none;
+//! - `{basename}.attrs.{column}` and `{basename}.attrs.{column}.offsets` for
+//!   an [`AttrType::Text`] column: the values of the nodes in id order, and
+//!   the offset of each node in them followed by their total length, as
+//!   little-endian `u64`s. A node without a value has an empty one.
+//!
+//! If a node gets more than one value in a column, the first one pushed is
+//! kept. [`NodeAttrs`] memory-maps the columns, so looking up the attribute
+//! of a node with [`NodeAttrs::get`] reads a few bytes.
+
+use crate::utils::{mmap, temp_dir};
+use anyhow::{ensure, Context, Result};
+use dsi_progress_logger::*;
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// The value of a node without a value in an [`AttrType::Int`] column.
+pub const MISSING_INT: u64 = u64::MAX;
+
+/// The type of the values of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttrType {
+    /// Unsigned integers, e.g. the size of a protein
+    Int,
+    /// UTF-8 strings, e.g. the description of a term
+    Text,
+}
+
+/// A column of the attributes, with its name and type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttrColumn {
+    /// The name of the column, e.g. `preferred_name`
+    pub name: String,
+    #[serde(rename = "type")]
+    pub attr_type: AttrType,
+}
+
+impl AttrColumn {
+    /// A column of integers.
+    pub fn int(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attr_type: AttrType::Int,
+        }
+    }
+
+    /// A column of strings.
+    pub fn text(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attr_type: AttrType::Text,
+        }
+    }
+}
+
+/// The value of an attribute of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttrValue<'a> {
+    Int(u64),
+    Text(&'a str),
+}
+
+impl AttrValue<'_> {
+    /// The type of the value.
+    pub fn attr_type(&self) -> AttrType {
+        match self {
+            AttrValue::Int(_) => AttrType::Int,
+            AttrValue::Text(_) => AttrType::Text,
+        }
+    }
+}
+
+impl fmt::Display for AttrValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttrValue::Int(value) => write!(f, "{}", value),
+            AttrValue::Text(value) => f.write_str(value),
+        }
+    }
+}
+
+/// The metadata of the attributes, stored in `{basename}.attrs.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttrsMetadata {
+    /// The number of nodes
+    pub num_nodes: usize,
+    /// The columns, in the order they were added
+    pub columns: Vec<AttrColumn>,
+}
+
+/// A sorted chunk of the values of a column, each stored as the node id as
+/// a little-endian `u32`, the length of the value as a little-endian `u32`
+/// and its bytes.
+struct Chunk {
+    file: io::BufReader<fs::File>,
+}
+
+impl Chunk {
+    /// Read the next node id and its value into `value`, if any.
+    fn next(&mut self, value: &mut Vec<u8>) -> Result<Option<usize>> {
+        let mut header = [0; 8];
+        match self.file.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let id = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let len = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+        value.resize(len, 0);
+        self.file.read_exact(value)?;
+        Ok(Some(id))
+    }
+}
+
+/// The values of a column pushed to an [`AttrsBuilder`].
+struct ColumnBuilder {
+    column: AttrColumn,
+    /// The node ids, with the ends of their values in `data`
+    values: Vec<(usize, usize)>,
+    data: Vec<u8>,
+    chunks: Vec<PathBuf>,
+}
+
+/// Sorts the attributes of the nodes given in any order using a bounded
+/// amount of memory, like [`crate::weights::WeightsBuilder`] does with the
+/// weights of the arcs.
+pub struct AttrsBuilder {
+    dir: PathBuf,
+    batch_size: usize,
+    /// The number of values in memory, over all the columns
+    num_values: usize,
+    columns: Vec<ColumnBuilder>,
+}
+
+impl AttrsBuilder {
+    /// A new builder which keeps up to `batch_size` values in memory, over
+    /// all the columns, and writes its temporary files in a new directory
+    /// inside `tmp_dir`.
+    pub fn new<P: AsRef<Path>>(tmp_dir: P, batch_size: usize) -> Self {
+        Self {
+            dir: PathBuf::from(temp_dir(tmp_dir)),
+            batch_size,
+            num_values: 0,
+            columns: Vec::new(),
+        }
+    }
+
+    /// Add the columns of a source. A column with the name of one already
+    /// added is shared, and must have the same type.
+    pub fn add_columns(&mut self, columns: &[AttrColumn]) -> Result<()> {
+        for column in columns {
+            ensure!(
+                !column.name.is_empty()
+                    && column
+                        .name
+                        .bytes()
+                        .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_'),
+                "Invalid attribute name {:?}",
+                column.name
+            );
+            match self
+                .columns
+                .iter()
+                .find(|other| other.column.name == column.name)
+            {
+                Some(other) => ensure!(
+                    other.column.attr_type == column.attr_type,
+                    "Attribute {} is both {:?} and {:?}",
+                    column.name,
+                    other.column.attr_type,
+                    column.attr_type
+                ),
+                None => self.columns.push(ColumnBuilder {
+                    column: column.clone(),
+                    values: Vec::new(),
+                    data: Vec::new(),
+                    chunks: Vec::new(),
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the value of an attribute of a node.
+    pub fn push(&mut self, id: usize, name: &str, value: AttrValue) -> Result<()> {
+        let index = self
+            .columns
+            .iter()
+            .position(|builder| builder.column.name == name)
+            .with_context(|| format!("Unknown attribute {}", name))?;
+        let builder = &mut self.columns[index];
+        ensure!(
+            builder.column.attr_type == value.attr_type(),
+            "Attribute {} is {:?}, got {:?}",
+            name,
+            builder.column.attr_type,
+            value
+        );
+        match value {
+            AttrValue::Int(value) => {
+                ensure!(
+                    value != MISSING_INT,
+                    "Attribute {} cannot be {}",
+                    name,
+                    value
+                );
+                builder.data.extend_from_slice(&value.to_le_bytes())
+            }
+            AttrValue::Text(value) => builder.data.extend_from_slice(value.as_bytes()),
+        }
+        builder.values.push((id, builder.data.len()));
+        self.num_values += 1;
+        if self.num_values >= self.batch_size {
+            for index in 0..self.columns.len() {
+                self.dump_batch(index)?;
+            }
+            self.num_values = 0;
+        }
+        Ok(())
+    }
+
+    /// Sort the values in memory of a column by node id, keeping the first
+    /// value of each node, and write them to a new chunk.
+    fn dump_batch(&mut self, index: usize) -> Result<()> {
+        let builder = &mut self.columns[index];
+        if builder.values.is_empty() {
+            return Ok(());
+        }
+        let mut order = (0..builder.values.len()).collect::<Vec<_>>();
+        // the sort is stable, so the first value of a node comes first
+        order.sort_by_key(|&i| builder.values[i].0);
+        let path = self
+            .dir
+            .join(format!("{}.{}", builder.column.name, builder.chunks.len()));
+        let mut file = io::BufWriter::new(fs::File::create(&path)?);
+        let mut last = None;
+        for i in order {
+            let (id, end) = builder.values[i];
+            if last == Some(id) {
+                continue;
+            }
+            last = Some(id);
+            let start = if i == 0 { 0 } else { builder.values[i - 1].1 };
+            file.write_all(&(id as u32).to_le_bytes())?;
+            file.write_all(&((end - start) as u32).to_le_bytes())?;
+            file.write_all(&builder.data[start..end])?;
+        }
+        file.flush()?;
+        builder.values.clear();
+        builder.data.clear();
+        builder.chunks.push(path);
+        Ok(())
+    }
+
+    /// Merge the values and write the columns with the given basename.
+    pub fn build<P: AsRef<Path>>(mut self, basename: P, num_nodes: usize) -> Result<AttrsMetadata> {
+        let basename = basename.as_ref().to_string_lossy().to_string();
+        for index in 0..self.columns.len() {
+            self.dump_batch(index)?;
+        }
+        for builder in &self.columns {
+            let mut pl = ProgressLogger::default();
+            pl.display_memory(true);
+            pl.start(format!(
+                "Writing {}.attrs.{}",
+                basename, builder.column.name
+            ));
+            let mut writer = ColumnWriter::new(
+                &format!("{}.attrs.{}", basename, builder.column.name),
+                builder.column.attr_type,
+            )?;
+            let mut chunks = builder
+                .chunks
+                .iter()
+                .map(|path| {
+                    Ok(Chunk {
+                        file: io::BufReader::new(fs::File::open(path)?),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            // the current value of each chunk
+            let mut values = vec![Vec::new(); chunks.len()];
+            let mut heap = BinaryHeap::new();
+            for (chunk_id, chunk) in chunks.iter_mut().enumerate() {
+                if let Some(id) = chunk.next(&mut values[chunk_id])? {
+                    heap.push(Reverse((id, chunk_id)));
+                }
+            }
+            // the chunks are in push order, so the first value of a node is
+            // the one of its first chunk
+            while let Some(Reverse((id, chunk_id))) = heap.pop() {
+                ensure!(id < num_nodes, "Node {} out of range", id);
+                if writer.next_id <= id {
+                    writer.push(id, &values[chunk_id])?;
+                    pl.light_update();
+                }
+                if let Some(next) = chunks[chunk_id].next(&mut values[chunk_id])? {
+                    heap.push(Reverse((next, chunk_id)));
+                }
+            }
+            writer.finish(num_nodes)?;
+            pl.done();
+        }
+        fs::remove_dir_all(&self.dir)?;
+
+        let metadata = AttrsMetadata {
+            num_nodes,
+            columns: self
+                .columns
+                .into_iter()
+                .map(|builder| builder.column)
+                .collect(),
+        };
+        serde_json::to_writer_pretty(
+            fs::File::create(format!("{}.attrs.json", basename))?,
+            &metadata,
+        )?;
+        Ok(metadata)
+    }
+}
+
+/// Writes the values of a column given in increasing order of node id.
+struct ColumnWriter {
+    attr_type: AttrType,
+    data: io::BufWriter<fs::File>,
+    /// The offsets of the text columns
+    offsets: Option<io::BufWriter<fs::File>>,
+    written: u64,
+    /// The next node whose value must be written
+    next_id: usize,
+}
+
+impl ColumnWriter {
+    fn new(path: &str, attr_type: AttrType) -> Result<Self> {
+        let offsets = match attr_type {
+            AttrType::Int => None,
+            AttrType::Text => Some(io::BufWriter::new(fs::File::create(format!(
+                "{}.offsets",
+                path
+            ))?)),
+        };
+        Ok(Self {
+            attr_type,
+            data: io::BufWriter::new(fs::File::create(path)?),
+            offsets,
+            written: 0,
+            next_id: 0,
+        })
+    }
+
+    /// Write the empty values of the nodes before `id`.
+    fn skip_to(&mut self, id: usize) -> Result<()> {
+        while self.next_id < id {
+            match &mut self.offsets {
+                Some(offsets) => offsets.write_all(&self.written.to_le_bytes())?,
+                None => self.data.write_all(&MISSING_INT.to_le_bytes())?,
+            }
+            self.next_id += 1;
+        }
+        Ok(())
+    }
+
+    /// Write the value of the node `id`, encoded as in a [`Chunk`].
+    fn push(&mut self, id: usize, value: &[u8]) -> Result<()> {
+        self.skip_to(id)?;
+        if let Some(offsets) = &mut self.offsets {
+            offsets.write_all(&self.written.to_le_bytes())?;
+        }
+        if self.attr_type == AttrType::Int {
+            ensure!(value.len() == 8, "Invalid integer attribute of node {}", id);
+        }
+        self.data.write_all(value)?;
+        self.written += value.len() as u64;
+        self.next_id += 1;
+        Ok(())
+    }
+
+    /// Write the remaining nodes.
+    fn finish(mut self, num_nodes: usize) -> Result<()> {
+        self.skip_to(num_nodes)?;
+        if let Some(offsets) = &mut self.offsets {
+            offsets.write_all(&self.written.to_le_bytes())?;
+            offsets.flush()?;
+        }
+        self.data.flush()?;
+        Ok(())
+    }
+}
+
+/// The memory-mapped files of a column.
+enum ColumnData {
+    Int(Mmap),
+    Text { data: Mmap, offsets: Mmap },
+}
+
+/// Read-only, memory-mapped access to the attributes of the nodes.
+pub struct NodeAttrs {
+    metadata: AttrsMetadata,
+    columns: Vec<ColumnData>,
+}
+
+impl NodeAttrs {
+    /// Load the attributes with the given basename, e.g. `res`.
+    pub fn load<P: AsRef<Path>>(basename: P) -> Result<Self> {
+        let basename = basename.as_ref().display();
+        let path = format!("{}.attrs.json", basename);
+        let metadata: AttrsMetadata = serde_json::from_reader(io::BufReader::new(
+            fs::File::open(&path).with_context(|| format!("Could not open {}", path))?,
+        ))
+        .with_context(|| format!("Could not parse {}", path))?;
+        let columns = metadata
+            .columns
+            .iter()
+            .map(|column| {
+                let path = format!("{}.attrs.{}", basename, column.name);
+                let data = mmap(&path)?;
+                Ok(match column.attr_type {
+                    AttrType::Int => {
+                        ensure!(
+                            data.len() == metadata.num_nodes * 8,
+                            "{} does not match the number of nodes",
+                            path
+                        );
+                        ColumnData::Int(data)
+                    }
+                    AttrType::Text => {
+                        let offsets = mmap(format!("{}.offsets", path))?;
+                        ensure!(
+                            offsets.len() == (metadata.num_nodes + 1) * 8,
+                            "{}.offsets does not match the number of nodes",
+                            path
+                        );
+                        ColumnData::Text { data, offsets }
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { metadata, columns })
+    }
+
+    /// The metadata of the attributes.
+    pub fn metadata(&self) -> &AttrsMetadata {
+        &self.metadata
+    }
+
+    /// The index of the column with the given name.
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.metadata
+            .columns
+            .iter()
+            .position(|column| column.name == name)
+    }
+
+    /// The value of the attribute `name` of a node, if it has one.
+    pub fn get(&self, id: usize, name: &str) -> Option<AttrValue<'_>> {
+        self.get_column(id, self.column(name)?)
+    }
+
+    /// Like [`NodeAttrs::get`], with the index of the column.
+    pub fn get_column(&self, id: usize, column: usize) -> Option<AttrValue<'_>> {
+        if id >= self.metadata.num_nodes {
+            return None;
+        }
+        match self.columns.get(column)? {
+            ColumnData::Int(data) => {
+                let value = u64::from_le_bytes(data[id * 8..id * 8 + 8].try_into().unwrap());
+                (value != MISSING_INT).then_some(AttrValue::Int(value))
+            }
+            ColumnData::Text { data, offsets } => {
+                let offset = |id: usize| {
+                    u64::from_le_bytes(offsets[id * 8..id * 8 + 8].try_into().unwrap()) as usize
+                };
+                let value = std::str::from_utf8(&data[offset(id)..offset(id + 1)]).ok()?;
+                (!value.is_empty()).then_some(AttrValue::Text(value))
+            }
+        }
+    }
+
+    /// The attributes of a node, with the names of their columns.
+    pub fn node(&self, id: usize) -> impl Iterator<Item = (&str, AttrValue<'_>)> {
+        self.metadata
+            .columns
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, column)| {
+                Some((column.name.as_str(), self.get_column(id, index)?))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let dir = temp_dir(std::env::temp_dir());
+        // a tiny batch, so that the duplicates are in different chunks
+        let mut attrs = AttrsBuilder::new(&dir, 2);
+        attrs.add_columns(&[AttrColumn::text("preferred_name"), AttrColumn::int("size")])?;
+        attrs.push(3, "preferred_name", AttrValue::Text("TP53"))?;
+        attrs.push(0, "preferred_name", AttrValue::Text("BRCA1"))?;
+        attrs.push(3, "size", AttrValue::Int(393))?;
+        attrs.push(3, "preferred_name", AttrValue::Text("P53"))?;
+        attrs.push(1, "size", AttrValue::Int(0))?;
+        attrs.push(1, "size", AttrValue::Int(7))?;
+        assert!(attrs.push(1, "size", AttrValue::Text("7")).is_err());
+        assert!(attrs.push(1, "length", AttrValue::Int(7)).is_err());
+        let basename = Path::new(&dir).join("res");
+        attrs.build(&basename, 5)?;
+
+        let attrs = NodeAttrs::load(&basename)?;
+        assert_eq!(attrs.metadata().num_nodes, 5);
+        assert_eq!(
+            attrs.get(0, "preferred_name"),
+            Some(AttrValue::Text("BRCA1"))
+        );
+        // the first value pushed wins
+        assert_eq!(
+            attrs.get(3, "preferred_name"),
+            Some(AttrValue::Text("TP53"))
+        );
+        assert_eq!(attrs.get(1, "size"), Some(AttrValue::Int(0)));
+        // the nodes without a value, and those out of range
+        assert_eq!(attrs.get(1, "preferred_name"), None);
+        assert_eq!(attrs.get(4, "preferred_name"), None);
+        assert_eq!(attrs.get(4, "size"), None);
+        assert_eq!(attrs.get(5, "size"), None);
+        assert_eq!(attrs.get(0, "length"), None);
+        assert_eq!(
+            attrs.node(3).collect::<Vec<_>>(),
+            [
+                ("preferred_name", AttrValue::Text("TP53")),
+                ("size", AttrValue::Int(393))
+            ]
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
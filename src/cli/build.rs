@@ -0,0 +1,310 @@
+//! Using the pre-built vocabulary, merge all files into a graph, with the
+//! edge types of its arcs in a parallel label file (see [`spw::labels`]), the
+//! weights of the arcs of each weighted source (see [`spw::weights`]) and the
+//! attributes of the nodes (see [`spw::attrs`])
+//!
+//! you may want to increase the maximum number of open files, expecially if
+//! your batch_size is small, i.e. you have little RAM
+//! ```
+//! sudo sysctl -w fs.file-max=10000000
+//! ```
+
+use anyhow::{ensure, Result};
+use clap::Args;
+use dsi_progress_logger::*;
+use spw::attrs::AttrsBuilder;
+use spw::config::Config;
+use spw::labels::{self, ArcLabelsWriter, EdgeTypes};
+use spw::missing::{MissingPolicy, MissingReport, VocabIds};
+use spw::sources::SendPushArc;
+use spw::utils::temp_dir;
+use spw::vocab::Vocab;
+use spw::weights::WeightsBuilder;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+
+use webgraph::graph::arc_list_graph::ArcListGraph;
+use webgraph::graph::bvgraph::parallel_compress_sequential_iter;
+use webgraph::prelude::*;
+
+use itertools::{Dedup, Itertools, KMerge};
+
+/// Merge the arcs of the enabled sources into a compressed graph, using the
+/// vocabulary built by `spw vocab`.
+#[derive(Args, Debug)]
+pub struct CliArgs {
+    /// How many arcs (16 bytes each) to sort in memory before dumping them,
+    /// in total over the parse threads and the weights of the arcs
+    #[arg(short, long)]
+    pub batch_size: Option<usize>,
+    /// A filter expression over the scores of the STRING links, e.g.
+    /// "experiments >= 400 OR database > 0", overriding the manifest; it
+    /// replaces the min_score of the manifest
+    #[arg(long)]
+    pub string_filter: Option<String>,
+}
+
+/// What each worker of the edge pass writes to: its own sorter of the arcs
+/// and, for the weighted sources, its own sorter of the weights.
+struct Shard {
+    sorted: SortPairs,
+    weights: Option<WeightsBuilder>,
+    /// The arcs of the current source that were pushed, and dropped
+    pushed_arcs: usize,
+    dropped_arcs: usize,
+}
+
+/// How many arcs a worker pushes between two updates of the progress logger.
+const PROGRESS_STEP: usize = 1 << 16;
+
+pub fn main(config: &Config, args: CliArgs) -> Result<()> {
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true);
+    pl.start("Creating the graph");
+
+    let mut config = config.clone();
+    if let Some(filter) = args.string_filter {
+        config.string.filter = Some(filter);
+    }
+    let registry = config.registry()?;
+    let output = config.output_basename();
+    println!(
+        "layers: {}, writing {}",
+        config.layers().join(", "),
+        output.display()
+    );
+    let vocab = Vocab::load(config.vocab_basename())?;
+    let num_nodes = vocab.len();
+    // the successors travel through the sort packed with their edge type
+    ensure!(
+        num_nodes <= labels::MAX_NODES,
+        "Too many nodes {}, at most {} are supported",
+        num_nodes,
+        labels::MAX_NODES
+    );
+    let batch_size = args.batch_size.unwrap_or(config.batch_size);
+    let num_shards = config.parse_threads();
+    // the shards share the memory of a batch, which is also split between
+    // their arcs and their weights if a source has any
+    let weighted = registry
+        .iter()
+        .any(|source| !source.weight_names().is_empty());
+    let buffers = num_shards * if weighted { 2 } else { 1 };
+    let shard_batch_size = (batch_size / buffers).max(1);
+    let mut shards = (0..num_shards)
+        .map(|_| {
+            Ok(Shard {
+                sorted: SortPairs::new(shard_batch_size, temp_dir(&config.tmp_dir))?,
+                weights: None,
+                pushed_arcs: 0,
+                dropped_arcs: 0,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let edge_types = Mutex::new(EdgeTypes::default());
+    let mut reports = Vec::new();
+    for source in registry.iter() {
+        let mut source_pl = ProgressLogger::default();
+        source_pl.display_memory(true);
+        source_pl.start(format!("Working on {}", source.name()));
+        let source_pl = Mutex::new(source_pl);
+        let ids = VocabIds::new(&vocab, source.name(), config.missing_policy(source.name()));
+        let weight_names = source.weight_names();
+        for shard in &mut shards {
+            shard.weights = (!weight_names.is_empty()).then(|| {
+                WeightsBuilder::new(&config.tmp_dir, shard_batch_size, weight_names.clone())
+            });
+            shard.pushed_arcs = 0;
+            shard.dropped_arcs = 0;
+        }
+        let mut pushes = shards
+            .iter_mut()
+            .map(|shard| {
+                let edge_types = &edge_types;
+                let source_pl = &source_pl;
+                let source_name = source.name();
+                // the edge types of the predicates this worker has seen, so
+                // that it only locks and allocates on the first sighting
+                let mut known = HashMap::<Box<str>, usize>::new();
+                Box::new(move |src, dst, predicate: &str, arc_weights: &[u16]| {
+                    match (src, dst) {
+                        (Some(src), Some(dst)) => {
+                            let edge_type = match known.get(predicate) {
+                                Some(&edge_type) => edge_type,
+                                None => {
+                                    let edge_type =
+                                        edge_types.lock().unwrap().id(source_name, predicate)?;
+                                    known.insert(predicate.into(), edge_type);
+                                    edge_type
+                                }
+                            };
+                            // the edge type travels with the successor through the sort
+                            shard.sorted.push(src, labels::pack(dst, edge_type))?;
+                            if let Some(weights) = &mut shard.weights {
+                                weights.push(src, dst, arc_weights)?;
+                            }
+                        }
+                        _ => shard.dropped_arcs += 1,
+                    }
+                    shard.pushed_arcs += 1;
+                    if shard.pushed_arcs.is_multiple_of(PROGRESS_STEP) {
+                        source_pl.lock().unwrap().update_with_count(PROGRESS_STEP);
+                    }
+                    Ok(())
+                }) as Box<SendPushArc>
+            })
+            .collect::<Vec<_>>();
+        let source_report = source.par_arcs(&ids, &mut pushes)?;
+        drop(pushes);
+        source_pl.into_inner().unwrap().done();
+        super::print_source_report(source.name(), &source_report);
+
+        let mut weights: Option<WeightsBuilder> = None;
+        for shard in &mut shards {
+            if let Some(shard_weights) = shard.weights.take() {
+                match &mut weights {
+                    Some(weights) => weights.append(shard_weights)?,
+                    None => weights = Some(shard_weights),
+                }
+            }
+        }
+        if let Some(weights) = weights {
+            let basename = format!("{}.{}", output.display(), source.name());
+            let metadata = weights.build(&basename, num_nodes)?;
+            println!(
+                "{}: {} weighted arcs with weights {}",
+                source.name(),
+                metadata.num_arcs,
+                metadata.columns.join(", ")
+            );
+        }
+        reports.push(ids.into_report(shards.iter().map(|shard| shard.dropped_arcs).sum()));
+    }
+    let mut attrs = AttrsBuilder::new(&config.tmp_dir, config.vocab_batch_size);
+    for source in registry.iter() {
+        let columns = source.attr_columns();
+        if columns.is_empty() {
+            continue;
+        }
+        attrs.add_columns(&columns)?;
+        let mut attrs_pl = ProgressLogger::default();
+        attrs_pl.display_memory(true);
+        attrs_pl.start(format!("Reading the attributes of {}", source.name()));
+        // the source added its nodes to the vocabulary, so they can only be
+        // missing if the vocabulary was built from other sources
+        let mut ids = VocabIds::new(&vocab, source.name(), MissingPolicy::Skip);
+        let mut dropped_attrs = 0;
+        let source_report = source.node_attrs(&mut ids, &mut |node_id, name, value| {
+            match node_id {
+                Some(node_id) => attrs.push(node_id, name, value)?,
+                None => dropped_attrs += 1,
+            }
+            attrs_pl.light_update();
+            Ok(())
+        })?;
+        attrs_pl.done();
+        super::print_source_report(source.name(), &source_report);
+        let mut attrs_report = ids.into_report(0);
+        attrs_report.dropped_attrs = dropped_attrs;
+        match reports
+            .iter_mut()
+            .find(|report| report.source == attrs_report.source)
+        {
+            Some(report) => report.merge(attrs_report),
+            None => reports.push(attrs_report),
+        }
+    }
+    for report in &reports {
+        print_missing(report);
+    }
+    serde_json::to_writer_pretty(
+        fs::File::create(format!("{}.missing.json", output.display()))?,
+        &reports,
+    )?;
+    let attrs_metadata = attrs.build(&output, num_nodes)?;
+    println!(
+        "attributes: {}",
+        attrs_metadata
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    // the ids of the edge types depend on the order of the threads, so they
+    // are renumbered in the order of the sources
+    let source_names = registry
+        .iter()
+        .map(|source| source.name())
+        .collect::<Vec<_>>();
+    let (edge_types, new_ids) = edge_types.into_inner().unwrap().into_sorted(&source_names);
+    let mut labels_pl = ProgressLogger::default();
+    labels_pl.display_memory(true);
+    labels_pl.start("Writing the edge types");
+    let mut labels = ArcLabelsWriter::new(&output, num_nodes)?;
+    for (src, packed, _) in merge_shards(&mut shards)? {
+        let (dst, edge_type) = labels::unpack(packed);
+        labels.push(src, dst, new_ids[edge_type])?;
+        labels_pl.light_update();
+    }
+    let labels_metadata = labels.finish(edge_types)?;
+    labels_pl.done();
+    for (id, edge_type) in labels_metadata.edge_types.iter().enumerate() {
+        println!("edge type {}: {}", id, edge_type);
+    }
+
+    // conver the iter to a graph
+    let g = ArcListGraph::new(
+        num_nodes,
+        merge_shards(&mut shards)?
+            .map(|(src, packed, _)| (src, labels::unpack(packed).0))
+            .dedup(),
+    );
+    // compress it
+    parallel_compress_sequential_iter::<
+        &ArcListGraph<Dedup<std::iter::Map<KMerge<KMergeIters<_>>, _>>>,
+        _,
+    >(
+        &output,
+        &g,
+        num_nodes,
+        CompFlags::default(),
+        config.threads,
+        temp_dir(&config.tmp_dir),
+    )
+    .unwrap();
+
+    pl.done();
+    Ok(())
+}
+
+/// Print a line with the counts and the samples of a missing report, if any
+/// arc was dropped.
+fn print_missing(report: &MissingReport) {
+    if report.dropped_arcs == 0 && report.dropped_attrs == 0 {
+        return;
+    }
+    println!(
+        "{}: dropped {} arcs and {} attributes, {} missing names, e.g. {}",
+        report.source,
+        report.dropped_arcs,
+        report.dropped_attrs,
+        report.missing_names,
+        report.samples.join(", ")
+    );
+}
+
+/// The sorted arcs of all the shards. [`SortPairs::iter`] dumps the pairs
+/// still in memory and opens its batch files anew, without removing them, so
+/// this can be called once for the labels and again for the graph.
+fn merge_shards(shards: &mut [Shard]) -> Result<KMerge<KMergeIters<BatchIterator>>> {
+    Ok(itertools::kmerge(
+        shards
+            .iter_mut()
+            .map(|shard| shard.sorted.iter())
+            .collect::<Result<Vec<_>>>()?,
+    ))
+}
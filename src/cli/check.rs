@@ -0,0 +1,80 @@
+//! Sanity checks on the input files.
+
+use anyhow::{ensure, Context, Result};
+use clap::Args;
+use dsi_progress_logger::*;
+use spw::config::Config;
+use spw::names;
+use spw::readers::*;
+use std::collections::BTreeSet;
+
+/// Check that the OMA and eggNOG files only refer to known species and
+/// proteins, for the enabled layers.
+#[derive(Args, Debug)]
+pub struct CliArgs {}
+
+/// Check that all OMA entry ids in the oma-groups file are prefixed by codes
+/// in the species file
+fn check_oma_groups_prefixes(config: &Config) -> Result<()> {
+    // load the OMA species codes
+    let mut oma_codes = BTreeSet::new();
+    for species in Records::<OmaSpecies>::open(config.oma_species())? {
+        oma_codes.insert(species?.code);
+    }
+
+    // check that all OMA groups are in the species file
+    for group in Records::<OmaGroup>::open(config.oma_groups())? {
+        for candidate_oma_entry in group?.entries {
+            let code = names::oma_species_of_entry(&candidate_oma_entry)
+                .with_context(|| format!("Malformed OMA entry {:?}", candidate_oma_entry))?;
+            ensure!(
+                oma_codes.contains(code),
+                "OMA entry {} has an unknown species code {}",
+                candidate_oma_entry,
+                code
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// check that in the eggnogg ortholog tsv, all the comma separated ids in the
+/// last column are valid STRING ppi ids
+fn check_eggnog(config: &Config) -> Result<()> {
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true);
+    pl.start("Loading the STRING protein info");
+    let mut ppi_ids = BTreeSet::new();
+    for info in Records::<StringInfo>::open(config.string_info())? {
+        ppi_ids.insert(info?.protein_id);
+        pl.light_update();
+    }
+    pl.done();
+
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true);
+    pl.start("Checking the eggNOG groups");
+    for group in Records::<EggnogGroup>::open(config.eggnog_groups())? {
+        for maybe_string_id in group?.proteins {
+            ensure!(
+                ppi_ids.contains(&maybe_string_id),
+                "Unknown STRING protein {} in the eggNOG groups",
+                maybe_string_id
+            );
+        }
+        pl.light_update();
+    }
+    pl.done();
+    Ok(())
+}
+
+pub fn main(config: &Config, _args: CliArgs) -> Result<()> {
+    if config.oma.enabled {
+        check_oma_groups_prefixes(config)?;
+    }
+    if config.eggnog.enabled {
+        check_eggnog(config)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! The subcommands of `spw`.
+//!
+//! The options shared by all the subcommands are in [`GlobalArgs`], and they
+//! override the corresponding fields of the build manifest.
+
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand};
+use spw::config::Config;
+use spw::sources::SourceReport;
+use std::path::PathBuf;
+
+mod build;
+mod check;
+mod ontologies;
+mod query;
+mod stats;
+mod vocab;
+
+/// Merge and compress the STRING ppi, OMA, eggNOG and KGX ontologies into a
+/// single webgraph.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    Vocab(vocab::CliArgs),
+    Build(build::CliArgs),
+    Check(check::CliArgs),
+    Stats(stats::CliArgs),
+    Ontologies(ontologies::CliArgs),
+    Query(query::CliArgs),
+}
+
+/// The options shared by all the subcommands.
+#[derive(Args, Debug)]
+pub struct GlobalArgs {
+    /// The build manifest, see `spw.example.toml`
+    #[arg(short, long, global = true)]
+    pub config: Option<PathBuf>,
+    /// The directory containing the input files
+    #[arg(short, long, global = true)]
+    pub input_dir: Option<PathBuf>,
+    /// The directory where to create the temporary files
+    #[arg(short, long, global = true)]
+    pub tmp_dir: Option<PathBuf>,
+    /// The basename of the vocabulary files
+    #[arg(long, global = true)]
+    pub vocab: Option<PathBuf>,
+    /// The basename of the compressed graph
+    #[arg(short, long, global = true)]
+    pub output: Option<PathBuf>,
+    /// How many threads to use
+    #[arg(short = 'j', long, global = true)]
+    pub threads: Option<usize>,
+    /// Increase the verbosity, can be repeated
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+    /// Only log errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+impl GlobalArgs {
+    /// The stderrlog verbosity: warnings and info by default.
+    pub fn verbosity(&self) -> usize {
+        if self.quiet {
+            0
+        } else {
+            2 + self.verbose as usize
+        }
+    }
+
+    /// Load the manifest, or the default one, and apply the overrides.
+    pub fn config(&self) -> Result<Config> {
+        let mut config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+        if let Some(input_dir) = &self.input_dir {
+            config.input_dir = input_dir.clone();
+        }
+        if let Some(tmp_dir) = &self.tmp_dir {
+            config.tmp_dir = tmp_dir.clone();
+        }
+        if let Some(vocab) = &self.vocab {
+            config.vocab = vocab.clone();
+        }
+        if let Some(output) = &self.output {
+            config.output = output.clone();
+        }
+        if let Some(threads) = self.threads {
+            config.threads = threads;
+        }
+        Ok(config)
+    }
+}
+
+impl Cli {
+    pub fn run(self) -> Result<()> {
+        let config = self.global.config()?;
+        match self.command {
+            Command::Vocab(args) => vocab::main(&config, args),
+            Command::Build(args) => build::main(&config, args),
+            Command::Check(args) => check::main(&config, args),
+            Command::Stats(args) => stats::main(&config, args),
+            Command::Ontologies(args) => ontologies::main(&config, args),
+            Command::Query(args) => query::main(&config, args),
+        }
+    }
+}
+
+/// Print what a source counted while reading its files.
+fn print_source_report(source: &str, report: &SourceReport) {
+    if let Some(links) = &report.links {
+        println!(
+            "{}: kept {} links, discarded {} ({})",
+            source, links.kept, links.discarded, links.selection
+        );
+    }
+    for predicates in &report.predicates {
+        println!("{}: predicate frequencies", predicates.path.display());
+        for (predicate, relation, count) in &predicates.frequencies {
+            println!(
+                "{}\t{}\t{}",
+                predicate,
+                relation.as_deref().unwrap_or("-"),
+                count
+            );
+        }
+    }
+    for malformed in &report.malformed {
+        let samples = malformed
+            .samples
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        println!(
+            "{}: {} lines with malformed quoted fields, kept as they are, e.g. lines {}",
+            malformed.path.display(),
+            malformed.count,
+            samples.join(", ")
+        );
+    }
+}
@@ -7,32 +7,37 @@
 //! The list of ontology prefixes will be saved in a JSON file.
 
 use anyhow::Result;
+use clap::Args;
 use dsi_progress_logger::*;
-use flate2::read::GzDecoder;
+use spw::config::Config;
+use spw::readers::*;
 use std::collections::BTreeSet;
 use std::fs;
-use std::io;
 use std::io::prelude::*;
+use std::path::PathBuf;
 
-fn parse_string_enrichment_terms(ontologie_codes: &mut BTreeSet<String>) -> Result<()> {
+/// Write the list of the ontology prefixes of the STRING enrichment terms.
+#[derive(Args, Debug)]
+pub struct CliArgs {
+    /// The JSON file where to write the list
+    #[arg(default_value = "ontology_codes.json")]
+    pub dst: PathBuf,
+}
+
+fn parse_string_enrichment_terms(
+    config: &Config,
+    ontologie_codes: &mut BTreeSet<String>,
+) -> Result<()> {
     let mut pl = ProgressLogger::default();
     pl.display_memory(true);
-    pl.start("Working on protein.enrichment.terms.v12.0.txt.gz");
-    let file = fs::File::open("../protein.enrichment.terms.v12.0.txt.gz")?;
-    let gz = io::BufReader::new(GzDecoder::new(io::BufReader::new(file)));
-
-    for line in gz.lines() {
-        let line = line?;
-        if line.starts_with('#') {
-            continue;
-        }
-
+    pl.start("Working on the STRING enrichment terms");
+    for term in Records::<StringEnrichmentTerm>::open(config.string_enrichment_terms())? {
         // We get the ontology node name
-        let ontology_node_name = line.split('\t').take(3).collect::<Vec<_>>()[2];
+        let ontology_node_name = term?.term;
 
         // We get the prefix of the ontology code
         let ontology_code = if ontology_node_name.contains('-') {
-            ontology_node_name.split('-').collect::<Vec<_>>()[0]
+            ontology_node_name.split('-').next().unwrap()
         } else if ontology_node_name.starts_with("IPR") {
             "IPR"
         } else if ontology_node_name.starts_with("PF") {
@@ -42,7 +47,7 @@ fn parse_string_enrichment_terms(ontologie_codes: &mut BTreeSet<String>) -> Resu
         } else if ontology_node_name.starts_with("WP") {
             "WP"
         } else if ontology_node_name.contains(':') {
-            ontology_node_name.split(':').collect::<Vec<_>>()[0]
+            ontology_node_name.split(':').next().unwrap()
         } else {
             unreachable!("how to split {}", ontology_node_name)
         };
@@ -55,21 +60,15 @@ fn parse_string_enrichment_terms(ontologie_codes: &mut BTreeSet<String>) -> Resu
     Ok(())
 }
 
-pub fn main() -> Result<()> {
-    stderrlog::new()
-        .verbosity(2)
-        .timestamp(stderrlog::Timestamp::Second)
-        .init()
-        .unwrap();
-
+pub fn main(config: &Config, args: CliArgs) -> Result<()> {
     // Initialize the ontology codes set
     let mut ontologie_codes = BTreeSet::new();
 
     // We parse the STRING enrichment terms file
-    parse_string_enrichment_terms(&mut ontologie_codes)?;
+    parse_string_enrichment_terms(config, &mut ontologie_codes)?;
 
     // We save the ontology codes as a JSON list
-    let mut ontology_codes_file = fs::File::create("ontology_codes.json")?;
+    let mut ontology_codes_file = fs::File::create(args.dst)?;
     ontology_codes_file.write_all(serde_json::to_string(&ontologie_codes)?.as_bytes())?;
 
     Ok(())
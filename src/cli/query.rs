@@ -0,0 +1,85 @@
+//! Look up nodes in the vocabulary.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use spw::attrs::NodeAttrs;
+use spw::config::Config;
+use spw::name_store::NameStore;
+use spw::names;
+use spw::vocab::Vocab;
+use std::io;
+use std::io::prelude::*;
+
+/// Print the ids of the given node names, or the names of the given node ids
+/// with `--ids`.
+///
+/// Without keys on the command line, reads one key per line from stdin.
+/// With `--ids` only the name store is loaded, not the hash function, and the
+/// display names are printed if the vocabulary has them. With `--attrs` the
+/// attributes of each node found are printed after it, as `column=value`.
+#[derive(Args, Debug)]
+pub struct CliArgs {
+    /// The keys are node ids instead of node names
+    #[arg(long)]
+    pub ids: bool,
+    /// Also print the attributes of the nodes, from the output of `spw build`
+    #[arg(long)]
+    pub attrs: bool,
+    /// The node names or ids to look up
+    pub keys: Vec<String>,
+}
+
+pub fn main(config: &Config, args: CliArgs) -> Result<()> {
+    let keys: Box<dyn Iterator<Item = Result<String>>> = if args.keys.is_empty() {
+        Box::new(io::stdin().lock().lines().map(|line| Ok(line?)))
+    } else {
+        Box::new(args.keys.into_iter().map(Ok))
+    };
+    let attrs = args
+        .attrs
+        .then(|| NodeAttrs::load(config.output_basename()))
+        .transpose()?;
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    if args.ids {
+        let names = NameStore::load_display(config.vocab_basename())?;
+        for key in keys {
+            let key = key?;
+            let node_id = key
+                .trim()
+                .parse::<usize>()
+                .with_context(|| format!("Invalid node id {}", key))?;
+            match names.get(node_id) {
+                Some(node_name) => {
+                    write!(out, "{}\t{}", node_id, node_name)?;
+                    write_attrs(&mut out, attrs.as_ref(), node_id)?;
+                }
+                None => writeln!(out, "{}\tnot found", node_id)?,
+            }
+        }
+    } else {
+        let vocab = Vocab::load(config.vocab_basename())?;
+        for key in keys {
+            let key = key?;
+            match vocab.id_of(&names::node(&key)) {
+                Some(node_id) => {
+                    write!(out, "{}\t{}", key, node_id)?;
+                    write_attrs(&mut out, attrs.as_ref(), node_id)?;
+                }
+                None => writeln!(out, "{}\tnot found", key)?,
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// End the line of a node with its attributes, if they were loaded.
+fn write_attrs(out: &mut impl Write, attrs: Option<&NodeAttrs>, node_id: usize) -> Result<()> {
+    if let Some(attrs) = attrs {
+        for (name, value) in attrs.node(node_id) {
+            write!(out, "\t{}={}", name, value)?;
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
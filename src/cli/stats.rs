@@ -0,0 +1,82 @@
+//! Distributions of the sizes of the orthologous groups.
+
+use anyhow::Result;
+use clap::Args;
+use dsi_progress_logger::*;
+use spw::config::Config;
+use spw::readers::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Write the distributions of the sizes of the OMA and eggNOG groups, and of
+/// the number of arcs their cliques would need, for the enabled layers.
+#[derive(Args, Debug)]
+pub struct CliArgs {
+    /// The directory where to write the distributions
+    #[arg(default_value = ".")]
+    pub dst: PathBuf,
+}
+
+/// Write a distribution as a `value\tcount` TSV.
+fn dump_dist(path: &Path, dist: &BTreeMap<usize, usize>) -> Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    for (k, v) in dist.iter() {
+        writeln!(file, "{}\t{}", k, v)?;
+    }
+    Ok(())
+}
+
+fn eggnogg_stats(config: &Config, dst: &Path) -> Result<()> {
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true);
+    pl.start("Working on the eggNOG groups");
+    let mut dist_raw: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut dist_edges: BTreeMap<usize, usize> = BTreeMap::new();
+
+    for group in Records::<EggnogGroup>::open(config.eggnog_groups())? {
+        let group_len = group?.num_proteins;
+        let num_edges = group_len + group_len * group_len.saturating_sub(1) + 1;
+
+        *dist_raw.entry(group_len).or_insert(0) += 1;
+        *dist_edges.entry(num_edges).or_insert(0) += 1;
+    }
+    pl.done();
+
+    dump_dist(&dst.join("egg_stats.tsv"), &dist_raw)?;
+    dump_dist(&dst.join("egg_stats_edges.tsv"), &dist_edges)?;
+    Ok(())
+}
+
+fn oma_stats(config: &Config, dst: &Path) -> Result<()> {
+    let mut dist_raw: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut dist_edges: BTreeMap<usize, usize> = BTreeMap::new();
+
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true);
+    pl.start("Working on the OMA groups");
+    for group in Records::<OmaGroup>::open(config.oma_groups())? {
+        let group_len = group?.entries.len();
+        let num_edges = group_len + group_len * group_len.saturating_sub(1) + 1;
+
+        *dist_raw.entry(group_len).or_insert(0) += 1;
+        *dist_edges.entry(num_edges).or_insert(0) += 1;
+    }
+    pl.done();
+
+    dump_dist(&dst.join("oma_stats.tsv"), &dist_raw)?;
+    dump_dist(&dst.join("oma_stats_edges.tsv"), &dist_edges)?;
+    Ok(())
+}
+
+pub fn main(config: &Config, args: CliArgs) -> Result<()> {
+    if config.oma.enabled {
+        oma_stats(config, &args.dst)?;
+    }
+    if config.eggnog.enabled {
+        eggnogg_stats(config, &args.dst)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! Scan all the files and build an unique vocabulary that maps all node names
+//! to node ids.
+
+use anyhow::Result;
+use clap::Args;
+use dsi_progress_logger::*;
+use spw::config::Config;
+use spw::missing::MissingPolicy;
+use spw::vocab::{print_node_types, print_vocab, VocabBuilder};
+
+/// Build the vocabulary of all the node names of the enabled sources.
+#[derive(Args, Debug)]
+pub struct CliArgs {}
+
+pub fn main(config: &Config, _args: CliArgs) -> Result<()> {
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true);
+    pl.start("Creating the vocabulary");
+
+    let registry = config.registry()?;
+    println!("layers: {}", config.layers().join(", "));
+    let mut builder = VocabBuilder::new(&config.tmp_dir, config.vocab_batch_size, config.id_policy)
+        .with_case(config.names.case)
+        .with_display_names(config.names.display_names);
+
+    for source in registry.iter() {
+        let mut source_pl = ProgressLogger::default();
+        source_pl.display_memory(true);
+        source_pl.start(format!("Working on {}", source.name()));
+        let source_report = source.node_names(&mut |node_type, node_name| {
+            builder.add(node_type, node_name)?;
+            source_pl.light_update();
+            Ok(())
+        })?;
+        source_pl.done();
+        super::print_source_report(source.name(), &source_report);
+
+        if config.missing_policy(source.name()) == MissingPolicy::AutoAdd {
+            let mut source_pl = ProgressLogger::default();
+            source_pl.display_memory(true);
+            source_pl.start(format!(
+                "Adding the endpoints of the arcs of {}",
+                source.name()
+            ));
+            let source_report =
+                source.arcs(&mut builder, &mut |_src, _dst, _predicate, _weights| {
+                    source_pl.light_update();
+                    Ok(())
+                })?;
+            source_pl.done();
+            super::print_source_report(source.name(), &source_report);
+        }
+    }
+
+    let metadata = builder.build(config.vocab_basename())?;
+    print_vocab(metadata.num_nodes);
+    print_node_types(&metadata);
+    if metadata.type_conflicts > 0 {
+        println!(
+            "{} node names were added with more than one type",
+            metadata.type_conflicts
+        );
+    }
+
+    pl.done();
+    Ok(())
+}
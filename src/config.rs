@@ -0,0 +1,453 @@
+//! The build manifest, describing which sources to use, where their files
+//! are, and where to write the outputs.
+//!
+//! The manifest is a TOML file, see `spw.example.toml`. Every field has a
+//! default that matches the original layout, with all the inputs in `..`.
+//! Relative input paths are resolved against `input_dir`, while the output
+//! paths are relative to the current directory.
+//!
+//! The sources are grouped in layers, KGX, STRING, OMA, eggNOG and UniProt,
+//! each enabled by its table. The default basenames of the vocabulary and of
+//! the graph contain [`LAYERS_PLACEHOLDER`], which stands for the enabled
+//! layers, so graphs built from different layers do not overwrite each other.
+
+use crate::missing::MissingPolicy;
+use crate::names::Case;
+use crate::sources::{eggnog, kgx, oma, string, uniprot, Registry, KGX_ONTOLOGIES};
+use crate::vocab::IdPolicy;
+use anyhow::{ensure, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Replaced in `vocab` and `output` by the enabled layers, e.g.
+/// `kgx-string-oma`.
+pub const LAYERS_PLACEHOLDER: &str = "{layers}";
+
+/// The build manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The directory containing the input files
+    pub input_dir: PathBuf,
+    /// The directory where to create the temporary files
+    pub tmp_dir: PathBuf,
+    /// The basename of the vocabulary files, which can contain
+    /// [`LAYERS_PLACEHOLDER`], see [`Config::vocab_basename`]
+    pub vocab: PathBuf,
+    /// The basename of the compressed graph, which can contain
+    /// [`LAYERS_PLACEHOLDER`], see [`Config::output_basename`]
+    pub output: PathBuf,
+    /// How many arcs to sort in memory before dumping them to a file, in
+    /// total over the parse threads and the weights of the arcs
+    pub batch_size: usize,
+    /// How many node names, or values of the node attributes over all their
+    /// columns, to sort in memory before dumping them to a file
+    pub vocab_batch_size: usize,
+    /// How the node ids are assigned
+    pub id_policy: IdPolicy,
+    /// How the node names are turned into vocabulary keys
+    pub names: NamesConfig,
+    /// How many threads to use for the compression
+    pub threads: usize,
+    /// How many threads parse the huge files and sort their arcs, 0 for
+    /// one per core
+    pub parse_threads: usize,
+    /// If present, only the sources with these names are used
+    pub sources: Option<Vec<String>>,
+    /// What to do with the arcs whose endpoints are not in the vocabulary
+    pub missing: MissingConfig,
+    pub string: StringConfig,
+    pub oma: OmaConfig,
+    pub eggnog: EggnogConfig,
+    pub uniprot: UniprotConfig,
+    pub kgx: KgxConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            input_dir: PathBuf::from(".."),
+            tmp_dir: PathBuf::from("/dfd/tmp"),
+            vocab: PathBuf::from(format!("../vocab.{}", LAYERS_PLACEHOLDER)),
+            output: PathBuf::from(format!("../res.{}", LAYERS_PLACEHOLDER)),
+            // a batch is 16GBs
+            batch_size: 1_000_000_000,
+            vocab_batch_size: 100_000_000,
+            id_policy: IdPolicy::default(),
+            names: NamesConfig::default(),
+            // the parallel compression is currently buggy
+            threads: 1,
+            parse_threads: 0,
+            sources: None,
+            missing: MissingConfig::default(),
+            string: StringConfig::default(),
+            oma: OmaConfig::default(),
+            eggnog: EggnogConfig::default(),
+            uniprot: UniprotConfig::default(),
+            kgx: KgxConfig::default(),
+        }
+    }
+}
+
+/// How the node names are stored in the vocabulary, see [`crate::names`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamesConfig {
+    /// How the keys are derived from the names
+    pub case: Case,
+    /// Whether to also store the names with their original case
+    pub display_names: bool,
+}
+
+/// The [`MissingPolicy`] of each source.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MissingConfig {
+    /// The policy of the sources not in `sources`
+    pub policy: MissingPolicy,
+    /// The policy of some sources, by name
+    pub sources: BTreeMap<String, MissingPolicy>,
+}
+
+/// The STRING files. The default file names are derived from `version`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StringConfig {
+    pub enabled: bool,
+    /// The STRING release, e.g. `v12.0`
+    pub version: String,
+    /// The minimum combined score of the links to keep, and so of the
+    /// weights stored for them; lower it to choose the threshold at query
+    /// time
+    pub min_score: u16,
+    /// A filter expression over the scores of the links, e.g.
+    /// `experiments >= 400 OR database > 0`, see [`crate::filter`]; when set
+    /// it replaces `min_score`
+    pub filter: Option<String>,
+    /// The scores stored as weights of the links, `combined_score` or the
+    /// names of the channels; empty to store none. Only the links kept by
+    /// `min_score` or `filter` are weighted
+    pub weights: Vec<String>,
+    /// The sources of the aliases that become nodes, e.g. `UniProt_AC` or
+    /// `Ensembl_gene`, each with the namespace of its nodes, e.g. `ENSEMBL`;
+    /// an empty namespace keeps the aliases as they are
+    pub alias_sources: BTreeMap<String, String>,
+    pub info: Option<PathBuf>,
+    pub aliases: Option<PathBuf>,
+    pub enrichment_terms: Option<PathBuf>,
+    pub links: Option<PathBuf>,
+}
+
+impl Default for StringConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            version: "v12.0".to_string(),
+            min_score: 700,
+            filter: None,
+            weights: vec![string::COMBINED_SCORE.to_string()],
+            // the UniProt ACs are shared with the OMA and UniProt layers
+            alias_sources: BTreeMap::from([("UniProt_AC".to_string(), String::new())]),
+            info: None,
+            aliases: None,
+            enrichment_terms: None,
+            links: None,
+        }
+    }
+}
+
+impl StringConfig {
+    /// The path of a STRING file, given its name without the version, e.g.
+    /// `protein.info`.
+    fn path(&self, path: &Option<PathBuf>, name: &str) -> PathBuf {
+        path.clone()
+            .unwrap_or_else(|| PathBuf::from(format!("{}.{}.txt.gz", name, self.version)))
+    }
+}
+
+/// The OMA browser files.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OmaConfig {
+    pub enabled: bool,
+    pub species: PathBuf,
+    pub groups: PathBuf,
+    pub uniprot: PathBuf,
+}
+
+impl Default for OmaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            species: PathBuf::from("oma-species.txt"),
+            groups: PathBuf::from("oma-groups.txt.gz"),
+            uniprot: PathBuf::from("oma-uniprot.txt.gz"),
+        }
+    }
+}
+
+/// The eggNOG 6 orthologous groups, disabled by default. Each group is
+/// linked from the NCBI taxon of its last common ancestor and is a star
+/// around its STRING proteins, which must be in the vocabulary, e.g. from the
+/// STRING layer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EggnogConfig {
+    pub enabled: bool,
+    pub groups: PathBuf,
+}
+
+impl Default for EggnogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            groups: PathBuf::from("e6.og2seqs_and_species.tsv"),
+        }
+    }
+}
+
+/// The UniProtKB entries, disabled by default.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UniprotConfig {
+    pub enabled: bool,
+    /// The flat file with the entries, e.g. the Swiss-Prot ones
+    pub entries: PathBuf,
+    /// The databases of the cross-references that become arcs
+    pub xrefs: Vec<String>,
+}
+
+impl Default for UniprotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            entries: PathBuf::from("uniprot_sprot.dat.gz"),
+            xrefs: ["GO", "InterPro", "Pfam"].map(String::from).to_vec(),
+        }
+    }
+}
+
+/// The KGX ontologies. For each ontology `x` we read `x_kgx_tsv_nodes.tsv`
+/// and `x_kgx_tsv_edges.tsv`, or with `archives` both from the
+/// `x_kgx_tsv.tar.gz` archive downloaded from kg-hub.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KgxConfig {
+    pub enabled: bool,
+    pub ontologies: Vec<String>,
+    /// Whether to read the ontologies from their `.tar.gz` archives instead
+    /// of the extracted TSV files
+    pub archives: bool,
+    /// Whether to add the relation of each edge to its predicate, e.g.
+    /// `biolink:related_to|RO:0002211`, so the edge types tell apart the
+    /// relations that share a predicate
+    pub relations: bool,
+}
+
+impl Default for KgxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ontologies: KGX_ONTOLOGIES.iter().map(|x| x.to_string()).collect(),
+            archives: false,
+            relations: true,
+        }
+    }
+}
+
+impl Config {
+    /// Load a manifest from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("Could not parse {}", path.display()))
+    }
+
+    /// Resolve an input path against `input_dir`.
+    pub fn input(&self, path: &Path) -> PathBuf {
+        self.input_dir.join(path)
+    }
+
+    /// The path of `protein.info.{version}.txt.gz`.
+    pub fn string_info(&self) -> PathBuf {
+        self.input(&self.string.path(&self.string.info, "protein.info"))
+    }
+
+    /// The path of `protein.aliases.{version}.txt.gz`.
+    pub fn string_aliases(&self) -> PathBuf {
+        self.input(&self.string.path(&self.string.aliases, "protein.aliases"))
+    }
+
+    /// The path of `protein.enrichment.terms.{version}.txt.gz`.
+    pub fn string_enrichment_terms(&self) -> PathBuf {
+        self.input(
+            &self
+                .string
+                .path(&self.string.enrichment_terms, "protein.enrichment.terms"),
+        )
+    }
+
+    /// The path of `protein.links.full.{version}.txt.gz`.
+    pub fn string_links(&self) -> PathBuf {
+        self.input(&self.string.path(&self.string.links, "protein.links.full"))
+    }
+
+    /// The path of the OMA species file.
+    pub fn oma_species(&self) -> PathBuf {
+        self.input(&self.oma.species)
+    }
+
+    /// The path of the OMA groups file.
+    pub fn oma_groups(&self) -> PathBuf {
+        self.input(&self.oma.groups)
+    }
+
+    /// The path of the OMA to UniProt mapping.
+    pub fn oma_uniprot(&self) -> PathBuf {
+        self.input(&self.oma.uniprot)
+    }
+
+    /// The path of the eggNOG groups file.
+    pub fn eggnog_groups(&self) -> PathBuf {
+        self.input(&self.eggnog.groups)
+    }
+
+    /// The names of the enabled layers, in the order they are scanned.
+    pub fn layers(&self) -> Vec<&'static str> {
+        [
+            ("kgx", self.kgx.enabled),
+            ("string", self.string.enabled),
+            ("oma", self.oma.enabled),
+            ("eggnog", self.eggnog.enabled),
+            ("uniprot", self.uniprot.enabled),
+        ]
+        .into_iter()
+        .filter_map(|(layer, enabled)| enabled.then_some(layer))
+        .collect()
+    }
+
+    /// Replace [`LAYERS_PLACEHOLDER`] in a basename with the enabled layers.
+    fn with_layers(&self, basename: &Path) -> PathBuf {
+        let layers = self.layers();
+        let layers = if layers.is_empty() {
+            "none".to_string()
+        } else {
+            layers.join("-")
+        };
+        PathBuf::from(
+            basename
+                .to_string_lossy()
+                .replace(LAYERS_PLACEHOLDER, &layers),
+        )
+    }
+
+    /// The basename of the vocabulary files, e.g. `../vocab.kgx-string-oma`.
+    pub fn vocab_basename(&self) -> PathBuf {
+        self.with_layers(&self.vocab)
+    }
+
+    /// The basename of the graph files, e.g. `../res.kgx-string-oma`.
+    pub fn output_basename(&self) -> PathBuf {
+        self.with_layers(&self.output)
+    }
+
+    /// The path of the UniProtKB entries.
+    pub fn uniprot_entries(&self) -> PathBuf {
+        self.input(&self.uniprot.entries)
+    }
+
+    /// How many threads parse the huge files, resolving `parse_threads`.
+    pub fn parse_threads(&self) -> usize {
+        match self.parse_threads {
+            0 => thread::available_parallelism().map_or(1, |threads| threads.get()),
+            threads => threads,
+        }
+    }
+
+    /// The [`MissingPolicy`] of a source.
+    pub fn missing_policy(&self, source: &str) -> MissingPolicy {
+        self.missing
+            .sources
+            .get(source)
+            .copied()
+            .unwrap_or(self.missing.policy)
+    }
+
+    /// The enabled sources, in the order they are scanned.
+    pub fn registry(&self) -> Result<Registry> {
+        let mut registry = Registry::new();
+        if self.kgx.enabled {
+            for ontology in &self.kgx.ontologies {
+                let source = if self.kgx.archives {
+                    kgx::Ontology::from_archive(
+                        ontology,
+                        self.input_dir.join(format!("{}_kgx_tsv.tar.gz", ontology)),
+                    )
+                } else {
+                    kgx::Ontology::new(
+                        ontology,
+                        self.input_dir
+                            .join(format!("{}_kgx_tsv_nodes.tsv", ontology)),
+                        self.input_dir
+                            .join(format!("{}_kgx_tsv_edges.tsv", ontology)),
+                    )
+                };
+                registry.register(source.with_relations(self.kgx.relations));
+            }
+        }
+        if self.string.enabled {
+            registry
+                .register(string::Info::new(self.string_info()))
+                .register(string::EnrichmentTerms::new(self.string_enrichment_terms()))
+                .register(string::Aliases::new(
+                    self.string_aliases(),
+                    &self.string.alias_sources,
+                ));
+        }
+        if self.oma.enabled {
+            registry
+                .register(oma::Species::new(self.oma_species()))
+                .register(oma::Groups::new(self.oma_groups()))
+                .register(oma::Uniprot::new(self.oma_uniprot()));
+        }
+        if self.eggnog.enabled {
+            registry.register(eggnog::Groups::new(self.eggnog_groups()));
+        }
+        if self.uniprot.enabled {
+            registry.register(uniprot::Entries::new(
+                self.uniprot_entries(),
+                self.uniprot.xrefs.clone(),
+            ));
+        }
+        if self.string.enabled {
+            registry.register(string::Links::new(
+                self.string_links(),
+                self.string.min_score,
+                self.string.filter.as_deref(),
+                self.string.weights.clone(),
+            )?);
+        }
+        for name in self.missing.sources.keys() {
+            ensure!(
+                registry.get(name).is_some(),
+                "Unknown or disabled source {} in missing.sources",
+                name
+            );
+        }
+        if let Some(sources) = &self.sources {
+            for name in sources {
+                ensure!(
+                    registry.get(name).is_some(),
+                    "Unknown or disabled source {}",
+                    name
+                );
+            }
+            registry.retain(|source| sources.iter().any(|name| name == source.name()));
+        }
+        Ok(registry)
+    }
+}
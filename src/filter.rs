@@ -0,0 +1,297 @@
+//! Boolean filter expressions over the named integer columns of a record,
+//! e.g. `experiments >= 400 OR database > 0` over the STRING links.
+//!
+//! An expression is made of comparisons between a column and an integer,
+//! with `<`, `<=`, `>`, `>=`, `==` and `!=`, combined with `AND` (or `&&`),
+//! `OR` (or `||`), `NOT` (or `!`) and parentheses. `NOT` binds tighter than
+//! `AND`, which binds tighter than `OR`. The keywords ignore case, while the
+//! column names must match the ones given to [`Filter::parse`].
+
+use anyhow::{bail, ensure, Context, Result};
+use std::fmt;
+
+/// A comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    fn eval(self, left: i64, right: i64) -> bool {
+        match self {
+            Op::Lt => left < right,
+            Op::Le => left <= right,
+            Op::Gt => left > right,
+            Op::Ge => left >= right,
+            Op::Eq => left == right,
+            Op::Ne => left != right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Column(String),
+    Number(i64),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let bytes = text.as_bytes();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        if c.is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+        let start = pos;
+        if c.is_ascii_alphabetic() || c == b'_' {
+            while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_') {
+                pos += 1;
+            }
+            let word = &text[start..pos];
+            tokens.push(match word.to_ascii_uppercase().as_str() {
+                "AND" => Token::And,
+                "OR" => Token::Or,
+                "NOT" => Token::Not,
+                _ => Token::Column(word.to_string()),
+            });
+            continue;
+        }
+        if c.is_ascii_digit() || c == b'-' {
+            pos += 1;
+            while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                pos += 1;
+            }
+            let number = &text[start..pos];
+            tokens.push(Token::Number(number.parse().with_context(|| {
+                format!("Invalid number {} at position {}", number, start)
+            })?));
+            continue;
+        }
+        let two = text.get(pos..pos + 2).unwrap_or("");
+        let (token, len) = match two {
+            "<=" => (Token::Op(Op::Le), 2),
+            ">=" => (Token::Op(Op::Ge), 2),
+            "==" => (Token::Op(Op::Eq), 2),
+            "!=" => (Token::Op(Op::Ne), 2),
+            "&&" => (Token::And, 2),
+            "||" => (Token::Or, 2),
+            _ => match c {
+                b'<' => (Token::Op(Op::Lt), 1),
+                b'>' => (Token::Op(Op::Gt), 1),
+                b'=' => (Token::Op(Op::Eq), 1),
+                b'!' => (Token::Not, 1),
+                b'(' => (Token::Open, 1),
+                b')' => (Token::Close, 1),
+                _ => bail!(
+                    "Unexpected character {:?} at position {}",
+                    text[pos..].chars().next().unwrap(),
+                    pos
+                ),
+            },
+        };
+        tokens.push(token);
+        pos += len;
+    }
+    Ok(tokens)
+}
+
+/// The parsed form of an expression, with the columns resolved to indices.
+#[derive(Debug, Clone)]
+enum Expr {
+    Compare { column: usize, op: Op, value: i64 },
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, values: &[u16]) -> bool {
+        match self {
+            Expr::Compare { column, op, value } => op.eval(values[*column] as i64, *value),
+            Expr::And(left, right) => left.eval(values) && right.eval(values),
+            Expr::Or(left, right) => left.eval(values) || right.eval(values),
+            Expr::Not(expr) => !expr.eval(values),
+        }
+    }
+}
+
+/// A recursive-descent parser over the tokens of an expression.
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    columns: &'a [&'a str],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                ensure!(self.next() == Some(Token::Close), "Expected )");
+                Ok(expr)
+            }
+            Some(Token::Column(name)) => {
+                let column = self
+                    .columns
+                    .iter()
+                    .position(|column| *column == name)
+                    .with_context(|| {
+                        format!(
+                            "Unknown column {}, expected one of {:?}",
+                            name, self.columns
+                        )
+                    })?;
+                let Some(Token::Op(op)) = self.next() else {
+                    bail!("Expected a comparison after {}", name);
+                };
+                let Some(Token::Number(value)) = self.next() else {
+                    bail!("Expected a number after {} {:?}", name, op);
+                };
+                Ok(Expr::Compare { column, op, value })
+            }
+            Some(token) => bail!("Unexpected {:?}", token),
+            None => bail!("Unexpected end of the expression"),
+        }
+    }
+}
+
+/// A compiled filter expression.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    text: String,
+    expr: Expr,
+}
+
+impl Filter {
+    /// Parse an expression over the given columns; [`Filter::eval`] takes
+    /// the values of the columns in the same order.
+    pub fn parse(text: &str, columns: &[&str]) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(text).with_context(|| format!("Could not parse filter {}", text))?,
+            pos: 0,
+            columns,
+        };
+        let expr = parser
+            .or()
+            .and_then(|expr| match parser.peek() {
+                None => Ok(expr),
+                Some(token) => bail!("Unexpected {:?}", token),
+            })
+            .with_context(|| format!("Could not parse filter {}", text))?;
+        Ok(Self {
+            text: text.to_string(),
+            expr,
+        })
+    }
+
+    /// Whether a record with the given column values passes the filter.
+    pub fn eval(&self, values: &[u16]) -> bool {
+        self.expr.eval(values)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: [&str; 3] = ["a", "b", "c"];
+
+    fn eval(text: &str, values: &[u16]) -> bool {
+        Filter::parse(text, &COLUMNS).unwrap().eval(values)
+    }
+
+    #[test]
+    fn test_precedence() {
+        // a OR (b AND c)
+        assert!(eval("a > 0 OR b > 0 AND c > 0", &[1, 0, 0]));
+        assert!(!eval("a > 0 OR b > 0 AND c > 0", &[0, 1, 0]));
+        assert!(eval("a > 0 OR b > 0 AND c > 0", &[0, 1, 1]));
+        assert!(eval("a > 0 || b > 0 && c > 0", &[1, 0, 0]));
+    }
+
+    #[test]
+    fn test_not() {
+        assert!(eval("NOT a > 0", &[0, 0, 0]));
+        assert!(!eval("not a > 0", &[1, 0, 0]));
+        assert!(eval("!a > 0 AND b == 1", &[0, 1, 0]));
+        assert!(eval("NOT NOT a >= 1", &[1, 0, 0]));
+    }
+
+    #[test]
+    fn test_parentheses() {
+        assert!(!eval("(a > 0 OR b > 0) AND c > 0", &[1, 0, 0]));
+        assert!(eval("(a > 0 OR b > 0) AND c > 0", &[1, 0, 1]));
+        assert!(eval("NOT (a > 0 AND b > 0)", &[1, 0, 0]));
+        assert!(Filter::parse("(a > 0", &COLUMNS).is_err());
+    }
+
+    #[test]
+    fn test_unknown_column() {
+        let err = Filter::parse("a > 0 OR d > 0", &COLUMNS).unwrap_err();
+        assert!(format!("{:#}", err).contains("Unknown column d"));
+    }
+
+    #[test]
+    fn test_trailing_garbage() {
+        assert!(Filter::parse("a > 0 b", &COLUMNS).is_err());
+        assert!(Filter::parse("a > 0 )", &COLUMNS).is_err());
+        assert!(Filter::parse("a > 0 AND", &COLUMNS).is_err());
+        assert!(Filter::parse("a > 0 $", &COLUMNS).is_err());
+    }
+}
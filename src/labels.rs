@@ -0,0 +1,339 @@
+//! The edge-type labels of the arcs of the graph.
+//!
+//! An [`EdgeType`] is a source and a predicate, e.g. `string_links` and
+//! `interacts_with`. The graph is simple, so an arc pushed by more than one
+//! source, or with more than one predicate, gets all of their edge types.
+//!
+//! The labels are stored next to the compressed graph, with its basename:
+//! - `{basename}.labels` and `{basename}.labels.offsets`: the ids of the edge
+//!   types of each arc, in the format of [`crate::arc_values`];
+//! - `{basename}.labels.json`: the [`LabelsMetadata`], with the edge types.
+//!
+//! [`ArcLabels`] answers [`ArcLabels::edge_type`] and iterates on the
+//! labelled successors of a node without loading the graph.
+
+use crate::arc_values::{ArcValues, ArcValuesWriter, Successors};
+use anyhow::{ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// How many low bits of a packed successor hold the edge type, see [`pack`].
+pub const EDGE_TYPE_BITS: u32 = 32;
+
+/// How many nodes a graph can have for its successors to be packed with
+/// [`pack`].
+pub const MAX_NODES: usize = 1 << (usize::BITS - EDGE_TYPE_BITS);
+
+/// Pack a successor and an edge type in a single `usize`, so that sorting
+/// the packed pairs sorts the arcs by successor and then by edge type.
+pub fn pack(dst: usize, edge_type: usize) -> usize {
+    debug_assert!(dst < MAX_NODES && edge_type < 1 << EDGE_TYPE_BITS);
+    (dst << EDGE_TYPE_BITS) | edge_type
+}
+
+/// The successor and the edge type of a value built by [`pack`].
+pub fn unpack(packed: usize) -> (usize, usize) {
+    (
+        packed >> EDGE_TYPE_BITS,
+        packed & ((1 << EDGE_TYPE_BITS) - 1),
+    )
+}
+
+/// The kind of an arc: the source that pushed it and its predicate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EdgeType {
+    pub source: String,
+    pub predicate: String,
+}
+
+impl fmt::Display for EdgeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.source, self.predicate)
+    }
+}
+
+/// Assigns ids to the edge types in order of appearance. As the arcs of a
+/// source may be pushed by several threads, [`EdgeTypes::into_sorted`] then
+/// renumbers them in a canonical order.
+#[derive(Debug, Default)]
+pub struct EdgeTypes {
+    types: Vec<EdgeType>,
+    ids: HashMap<String, HashMap<String, usize>>,
+}
+
+impl EdgeTypes {
+    /// The id of an edge type, assigning a new one if needed.
+    pub fn id(&mut self, source: &str, predicate: &str) -> Result<usize> {
+        if let Some(id) = self
+            .ids
+            .get(source)
+            .and_then(|predicates| predicates.get(predicate))
+        {
+            return Ok(*id);
+        }
+        let id = self.types.len();
+        ensure!(
+            id < 1 << EDGE_TYPE_BITS,
+            "Too many edge types, at most {} are supported",
+            1_usize << EDGE_TYPE_BITS
+        );
+        self.types.push(EdgeType {
+            source: source.to_string(),
+            predicate: predicate.to_string(),
+        });
+        self.ids
+            .entry(source.to_string())
+            .or_default()
+            .insert(predicate.to_string(), id);
+        Ok(id)
+    }
+
+    /// The edge types, by id.
+    pub fn into_vec(self) -> Vec<EdgeType> {
+        self.types
+    }
+
+    /// The edge types sorted by the position of their source in `sources`
+    /// and then by predicate, with the new id of each old id.
+    pub fn into_sorted(self, sources: &[&str]) -> (Vec<EdgeType>, Vec<usize>) {
+        let position = |source: &str| sources.iter().position(|name| *name == source);
+        let mut order = (0..self.types.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&self.types[a], &self.types[b]);
+            (position(&a.source), &a.predicate).cmp(&(position(&b.source), &b.predicate))
+        });
+        let mut new_ids = vec![0; order.len()];
+        for (new_id, &old_id) in order.iter().enumerate() {
+            new_ids[old_id] = new_id;
+        }
+        let types = order
+            .into_iter()
+            .map(|old_id| self.types[old_id].clone())
+            .collect();
+        (types, new_ids)
+    }
+}
+
+/// The metadata of the labels, stored in `{basename}.labels.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelsMetadata {
+    /// The number of nodes
+    pub num_nodes: usize,
+    /// The number of arcs, i.e. of distinct successor pairs
+    pub num_arcs: usize,
+    /// The edge types, by id
+    pub edge_types: Vec<EdgeType>,
+}
+
+/// Writes the labels of arcs given in increasing order of source and
+/// successor, possibly with repetitions. The edge types of an arc can be
+/// given in any order, and are stored sorted.
+pub struct ArcLabelsWriter {
+    basename: String,
+    values: ArcValuesWriter,
+    num_nodes: usize,
+    /// The last arc and its edge types, not yet written
+    arc: Option<(usize, usize)>,
+    edge_types: Vec<usize>,
+}
+
+impl ArcLabelsWriter {
+    /// Create the files of the labels with the given basename.
+    pub fn new<P: AsRef<Path>>(basename: P, num_nodes: usize) -> Result<Self> {
+        let basename = basename.as_ref().to_string_lossy().to_string();
+        Ok(Self {
+            values: ArcValuesWriter::new(&format!("{}.labels", basename), num_nodes)?,
+            basename,
+            num_nodes,
+            arc: None,
+            edge_types: Vec::new(),
+        })
+    }
+
+    /// Add an edge type to the arc from `src` to `dst`.
+    pub fn push(&mut self, src: usize, dst: usize, edge_type: usize) -> Result<()> {
+        if let Some(arc) = self.arc {
+            ensure!(arc <= (src, dst), "Arc {} -> {} out of order", src, dst);
+            if arc != (src, dst) {
+                self.write_arc()?;
+            }
+        }
+        self.arc = Some((src, dst));
+        self.edge_types.push(edge_type);
+        Ok(())
+    }
+
+    /// Write the distinct edge types of the last arc.
+    fn write_arc(&mut self) -> Result<()> {
+        if let Some((src, dst)) = self.arc {
+            self.edge_types.sort_unstable();
+            self.edge_types.dedup();
+            self.values.push(src, dst, &self.edge_types)?;
+            self.edge_types.clear();
+        }
+        Ok(())
+    }
+
+    /// Write the remaining nodes and the metadata with the given edge types.
+    pub fn finish(mut self, edge_types: Vec<EdgeType>) -> Result<LabelsMetadata> {
+        self.write_arc()?;
+        let metadata = LabelsMetadata {
+            num_nodes: self.num_nodes,
+            num_arcs: self.values.finish()?,
+            edge_types,
+        };
+        serde_json::to_writer_pretty(
+            fs::File::create(format!("{}.labels.json", self.basename))?,
+            &metadata,
+        )?;
+        Ok(metadata)
+    }
+}
+
+/// Read-only, memory-mapped access to the labels of the arcs.
+pub struct ArcLabels {
+    metadata: LabelsMetadata,
+    values: ArcValues,
+}
+
+impl ArcLabels {
+    /// Load the labels of the graph with the given basename.
+    pub fn load<P: AsRef<Path>>(basename: P) -> Result<Self> {
+        let basename = basename.as_ref().display();
+        let path = format!("{}.labels.json", basename);
+        let metadata: LabelsMetadata = serde_json::from_reader(io::BufReader::new(
+            fs::File::open(&path).with_context(|| format!("Could not open {}", path))?,
+        ))
+        .with_context(|| format!("Could not parse {}", path))?;
+        let values = ArcValues::load(&format!("{}.labels", basename), metadata.num_nodes)?;
+        Ok(Self { metadata, values })
+    }
+
+    /// The metadata of the labels.
+    pub fn metadata(&self) -> &LabelsMetadata {
+        &self.metadata
+    }
+
+    /// The number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.metadata.num_nodes
+    }
+
+    /// The edge type with the given id.
+    pub fn edge_type_name(&self, edge_type: usize) -> Option<&EdgeType> {
+        self.metadata.edge_types.get(edge_type)
+    }
+
+    /// The successors of a node in increasing order, each with the ids of
+    /// the edge types of the arc.
+    pub fn labelled_successors(&self, node: usize) -> Successors<'_> {
+        self.values.successors(node)
+    }
+
+    /// The number of successors of a node.
+    pub fn outdegree(&self, node: usize) -> usize {
+        self.labelled_successors(node).len()
+    }
+
+    /// The ids of the edge types of the arc from `src` to `dst`, empty if
+    /// there is no such arc.
+    pub fn edge_types(&self, src: usize, dst: usize) -> Vec<usize> {
+        self.values.values(src, dst).unwrap_or_default()
+    }
+
+    /// The id of the first edge type of the arc from `src` to `dst`, if
+    /// there is such an arc.
+    pub fn edge_type(&self, src: usize, dst: usize) -> Option<usize> {
+        self.edge_types(src, dst).first().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir;
+
+    #[test]
+    fn test_pack() {
+        let max_edge_type = (1 << EDGE_TYPE_BITS) - 1;
+        for (dst, edge_type) in [
+            (0, 0),
+            (MAX_NODES - 1, 0),
+            (0, max_edge_type),
+            (MAX_NODES - 1, max_edge_type),
+        ] {
+            assert_eq!(unpack(pack(dst, edge_type)), (dst, edge_type));
+        }
+        // the packed pairs sort by successor and then by edge type
+        assert!(pack(1, max_edge_type) < pack(2, 0));
+        assert!(pack(MAX_NODES - 2, max_edge_type) < pack(MAX_NODES - 1, 0));
+    }
+
+    #[test]
+    fn test_into_sorted() -> Result<()> {
+        let mut edge_types = EdgeTypes::default();
+        assert_eq!(edge_types.id("string_links", "interacts_with")?, 0);
+        assert_eq!(edge_types.id("go", "biolink:part_of")?, 1);
+        assert_eq!(edge_types.id("go", "biolink:subclass_of")?, 2);
+        assert_eq!(edge_types.id("go", "biolink:part_of")?, 1);
+        assert_eq!(edge_types.id("eggnog", "member_of")?, 3);
+        let (types, new_ids) = edge_types.into_sorted(&["go", "string_links"]);
+        assert_eq!(
+            types.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                // the sources not in the list come first
+                "eggnog/member_of",
+                "go/biolink:part_of",
+                "go/biolink:subclass_of",
+                "string_links/interacts_with",
+            ]
+        );
+        assert_eq!(new_ids, [3, 1, 2, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let dir = temp_dir(std::env::temp_dir());
+        let basename = Path::new(&dir).join("res");
+        let mut writer = ArcLabelsWriter::new(&basename, 5)?;
+        writer.push(0, 3, 1)?;
+        writer.push(0, 3, 0)?;
+        writer.push(0, 3, 1)?;
+        writer.push(0, 4, 2)?;
+        writer.push(3, 0, 0)?;
+        assert!(writer.push(2, 0, 0).is_err());
+        let edge_types = (0..3)
+            .map(|i| EdgeType {
+                source: "go".to_string(),
+                predicate: i.to_string(),
+            })
+            .collect::<Vec<_>>();
+        let metadata = writer.finish(edge_types.clone())?;
+        assert_eq!(metadata.num_arcs, 3);
+
+        let labels = ArcLabels::load(&basename)?;
+        // one offset per node, and the end
+        let offsets = fs::metadata(format!("{}.labels.offsets", basename.display()))?;
+        assert_eq!(offsets.len(), 6 * 8);
+        assert_eq!(labels.num_nodes(), 5);
+        assert_eq!(
+            labels.labelled_successors(0).collect::<Vec<_>>(),
+            [(3, vec![0, 1]), (4, vec![2])]
+        );
+        assert_eq!(labels.edge_types(0, 3), [0, 1]);
+        assert_eq!(labels.edge_type(3, 0), Some(0));
+        assert_eq!(labels.edge_type(0, 2), None);
+        for node in [1, 2, 4, 5] {
+            assert_eq!(labels.outdegree(node), 0);
+        }
+        assert_eq!(labels.edge_type_name(2), Some(&edge_types[2]));
+        assert_eq!(labels.edge_type_name(3), None);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
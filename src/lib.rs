@@ -0,0 +1,22 @@
+//! Library shared by all the binaries that merge the STRING ppi, OMA, eggNOG
+//! and KGX ontologies into a single webgraph.
+//!
+//! Every input file has a reader in [`readers`] that yields typed records,
+//! and every dataset is a [`sources::Source`] that turns those records into
+//! node names and arcs, so that the vocabulary pass and the edge pass are
+//! guaranteed to see the same node names.
+
+pub mod arc_values;
+pub mod attrs;
+pub mod config;
+pub mod filter;
+pub mod labels;
+pub mod missing;
+pub mod name_store;
+pub mod names;
+pub mod node_type;
+pub mod readers;
+pub mod sources;
+pub mod utils;
+pub mod vocab;
+pub mod weights;
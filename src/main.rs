@@ -0,0 +1,15 @@
+//! The `spw` command line tool, see `spw --help`.
+
+use anyhow::Result;
+use clap::Parser;
+
+mod cli;
+
+pub fn main() -> Result<()> {
+    let args = cli::Cli::parse();
+    stderrlog::new()
+        .verbosity(args.global.verbosity())
+        .timestamp(stderrlog::Timestamp::Second)
+        .init()?;
+    args.run()
+}
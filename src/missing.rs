@@ -0,0 +1,158 @@
+//! What to do with the arcs whose endpoints are not in the vocabulary.
+//!
+//! Each source has a [`MissingPolicy`]. With [`MissingPolicy::Fail`] the
+//! build stops at the first missing name, with [`MissingPolicy::Skip`] the
+//! arcs are dropped and counted in a [`MissingReport`], and with
+//! [`MissingPolicy::AutoAdd`] the vocabulary pass also adds the endpoints of
+//! the arcs of the source, so no name can be missing.
+
+use crate::node_type::NodeType;
+use crate::sources::{NodeIds, SharedNodeIds};
+use crate::vocab::{node_id, Vocab};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// How many distinct missing names a [`MissingReport`] keeps as samples.
+pub const NUM_SAMPLES: usize = 10;
+
+/// What to do with an arc whose endpoint is not in the vocabulary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingPolicy {
+    /// Stop the build with an error.
+    #[default]
+    Fail,
+    /// Drop the arc and count it.
+    Skip,
+    /// Add the endpoints of the arcs to the vocabulary when building it.
+    AutoAdd,
+}
+
+/// The arcs and the node attributes of a source dropped because of missing
+/// names.
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingReport {
+    /// The name of the source
+    pub source: String,
+    pub policy: MissingPolicy,
+    /// How many times a name was not found
+    pub missing_names: usize,
+    /// How many arcs were dropped
+    pub dropped_arcs: usize,
+    /// How many attributes were dropped
+    pub dropped_attrs: usize,
+    /// Up to [`NUM_SAMPLES`] of the names that were not found
+    pub samples: Vec<String>,
+}
+
+impl MissingReport {
+    /// Add the counts and the samples of another report of the same source,
+    /// e.g. the one of its attributes.
+    pub fn merge(&mut self, other: MissingReport) {
+        self.missing_names += other.missing_names;
+        self.dropped_arcs += other.dropped_arcs;
+        self.dropped_attrs += other.dropped_attrs;
+        for sample in other.samples {
+            if self.samples.len() < NUM_SAMPLES && !self.samples.contains(&sample) {
+                self.samples.push(sample);
+            }
+        }
+    }
+}
+
+/// Resolves the endpoints of the arcs of a source in a vocabulary, following
+/// the policy of the source. It can be shared by the threads of
+/// [`crate::sources::Source::par_arcs`].
+pub struct VocabIds<'a> {
+    vocab: &'a Vocab,
+    source: String,
+    policy: MissingPolicy,
+    missing_names: AtomicUsize,
+    samples: Mutex<Vec<String>>,
+}
+
+impl<'a> VocabIds<'a> {
+    pub fn new(vocab: &'a Vocab, source: &str, policy: MissingPolicy) -> Self {
+        Self {
+            vocab,
+            source: source.to_string(),
+            policy,
+            missing_names: AtomicUsize::new(0),
+            samples: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Count a missing name and keep it as a sample.
+    fn skip(&self, node_name: &str) {
+        self.missing_names.fetch_add(1, Ordering::Relaxed);
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() < NUM_SAMPLES && !samples.iter().any(|sample| sample == node_name) {
+            samples.push(node_name.to_string());
+        }
+    }
+
+    /// The report of the missing names, given the number of dropped arcs.
+    pub fn into_report(self, dropped_arcs: usize) -> MissingReport {
+        MissingReport {
+            source: self.source,
+            policy: self.policy,
+            missing_names: self.missing_names.into_inner(),
+            dropped_arcs,
+            dropped_attrs: 0,
+            samples: self.samples.into_inner().unwrap(),
+        }
+    }
+}
+
+impl SharedNodeIds for VocabIds<'_> {
+    fn get(&self, _node_type: NodeType, node_name: &str) -> Result<Option<usize>> {
+        if let Some(node_id) = self.vocab.id_of(node_name) {
+            return Ok(Some(node_id));
+        }
+        match self.policy {
+            MissingPolicy::Fail => node_id(self.vocab, node_name).map(Some).with_context(|| {
+                format!(
+                    "Missing node in source {}, set its missing policy to skip to drop the arc",
+                    self.source
+                )
+            }),
+            MissingPolicy::Skip => {
+                self.skip(node_name);
+                Ok(None)
+            }
+            MissingPolicy::AutoAdd => bail!(
+                "Could not map {} of source {}, was the vocabulary built with the auto_add policy?",
+                node_name,
+                self.source
+            ),
+        }
+    }
+
+    /// Malformed names are never added to the vocabulary, so with
+    /// [`MissingPolicy::AutoAdd`] they are skipped too.
+    fn malformed(&self, _node_type: NodeType, node_name: &str) -> Result<Option<usize>> {
+        match self.policy {
+            MissingPolicy::Fail => bail!(
+                "Malformed node {:?} in source {}, set its missing policy to skip to drop the arc",
+                node_name,
+                self.source
+            ),
+            MissingPolicy::Skip | MissingPolicy::AutoAdd => {
+                self.skip(node_name);
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl NodeIds for VocabIds<'_> {
+    fn get(&mut self, node_type: NodeType, node_name: &str) -> Result<Option<usize>> {
+        SharedNodeIds::get(self, node_type, node_name)
+    }
+
+    fn malformed(&mut self, node_type: NodeType, node_name: &str) -> Result<Option<usize>> {
+        SharedNodeIds::malformed(self, node_type, node_name)
+    }
+}
@@ -0,0 +1,336 @@
+//! An indexed, memory-mapped store of the node names in id order.
+//!
+//! The store is made of two files sharing a basename with the vocabulary:
+//! - `{basename}.names`: the names front-coded in buckets of [`BUCKET_SIZE`]
+//!   names. The first name of a bucket is stored as its length, as a LEB128
+//!   varint, followed by its bytes; every other name as the length of the
+//!   prefix it shares with the previous one, the length of the rest, and the
+//!   bytes of the rest;
+//! - `{basename}.names.offsets`: the offset of each bucket in
+//!   `{basename}.names`, as a little-endian `u64`.
+//!
+//! Getting a name decodes at most [`BUCKET_SIZE`] names. The store does not
+//! need the hash function of the vocabulary, so tools that only label node
+//! ids can load it with [`NameStore::load`].
+
+use crate::utils::{mmap, read_varint, write_varint};
+use crate::vocab::VocabMetadata;
+use anyhow::{ensure, Result};
+use memmap2::Mmap;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+/// How many names are front-coded together; the first one is stored in full.
+pub const BUCKET_SIZE: usize = 16;
+
+/// Decode the name starting at `pos` into `name`, which must contain the
+/// previous name of the bucket unless `first` is true.
+fn decode(data: &[u8], pos: &mut usize, first: bool, name: &mut Vec<u8>) {
+    let common = if first { 0 } else { read_varint(data, pos) };
+    let len = read_varint(data, pos);
+    name.truncate(common);
+    name.extend_from_slice(&data[*pos..*pos + len]);
+    *pos += len;
+}
+
+/// Writes a [`NameStore`], front-coding the names in the order they are
+/// pushed.
+pub struct NameStoreWriter {
+    names: io::BufWriter<fs::File>,
+    offsets: io::BufWriter<fs::File>,
+    written: u64,
+    previous: Vec<u8>,
+    buffer: Vec<u8>,
+    len: usize,
+}
+
+impl NameStoreWriter {
+    /// Create the files of the store with the given basename.
+    pub fn new<P: AsRef<Path>>(basename: P) -> Result<Self> {
+        let basename = basename.as_ref().display();
+        Ok(Self {
+            names: io::BufWriter::new(fs::File::create(format!("{}.names", basename))?),
+            offsets: io::BufWriter::new(fs::File::create(format!("{}.names.offsets", basename))?),
+            written: 0,
+            previous: Vec::new(),
+            buffer: Vec::new(),
+            len: 0,
+        })
+    }
+
+    /// Add the name of the next id.
+    pub fn push(&mut self, name: &[u8]) -> Result<()> {
+        self.buffer.clear();
+        if self.len.is_multiple_of(BUCKET_SIZE) {
+            self.offsets.write_all(&self.written.to_le_bytes())?;
+            write_varint(&mut self.buffer, name.len());
+            self.buffer.extend_from_slice(name);
+        } else {
+            let common = self
+                .previous
+                .iter()
+                .zip(name)
+                .take_while(|(a, b)| a == b)
+                .count();
+            write_varint(&mut self.buffer, common);
+            write_varint(&mut self.buffer, name.len() - common);
+            self.buffer.extend_from_slice(&name[common..]);
+        }
+        self.names.write_all(&self.buffer)?;
+        self.written += self.buffer.len() as u64;
+        self.previous.clear();
+        self.previous.extend_from_slice(name);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// The number of names pushed so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no name was pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Flush the files, returning the number of names.
+    pub fn finish(mut self) -> Result<usize> {
+        self.names.flush()?;
+        self.offsets.flush()?;
+        Ok(self.len)
+    }
+}
+
+/// Read-only, memory-mapped access to the node names by id.
+pub struct NameStore {
+    names: Mmap,
+    offsets: Mmap,
+    len: usize,
+}
+
+impl NameStore {
+    /// Load the store of the vocabulary with the given basename, taking the
+    /// number of names from `{basename}.json`.
+    pub fn load<P: AsRef<Path>>(basename: P) -> Result<Self> {
+        let metadata = VocabMetadata::load(&basename)?;
+        Self::load_with_len(basename, metadata.num_nodes)
+    }
+
+    /// Load the display names of the vocabulary with the given basename, or
+    /// its keys if it has no display names.
+    pub fn load_display<P: AsRef<Path>>(basename: P) -> Result<Self> {
+        let metadata = VocabMetadata::load(&basename)?;
+        if metadata.display_names {
+            Self::load_with_len(
+                format!("{}.display", basename.as_ref().display()),
+                metadata.num_nodes,
+            )
+        } else {
+            Self::load_with_len(basename, metadata.num_nodes)
+        }
+    }
+
+    /// Load the store with the given basename and number of names.
+    pub fn load_with_len<P: AsRef<Path>>(basename: P, len: usize) -> Result<Self> {
+        let basename = basename.as_ref().display();
+        let names = mmap(format!("{}.names", basename))?;
+        let offsets = mmap(format!("{}.names.offsets", basename))?;
+        ensure!(
+            offsets.len() / 8 == len.div_ceil(BUCKET_SIZE),
+            "The offsets of {}.names do not match the number of nodes",
+            basename
+        );
+        Ok(Self {
+            names,
+            offsets,
+            len,
+        })
+    }
+
+    /// The number of names.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the store is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decode the name of the given id into `name`, returning false if the
+    /// id is out of range.
+    pub fn get_into(&self, id: usize, name: &mut Vec<u8>) -> bool {
+        if id >= self.len {
+            return false;
+        }
+        let bucket = id / BUCKET_SIZE;
+        let mut pos =
+            u64::from_le_bytes(self.offsets[bucket * 8..bucket * 8 + 8].try_into().unwrap())
+                as usize;
+        decode(&self.names, &mut pos, true, name);
+        for _ in 0..id % BUCKET_SIZE {
+            decode(&self.names, &mut pos, false, name);
+        }
+        true
+    }
+
+    /// Whether the name of the given id is `key`. The name is compared while
+    /// it is decoded, so unlike [`NameStore::get_into`] no buffer is needed.
+    pub fn matches(&self, id: usize, key: &[u8]) -> bool {
+        if id >= self.len {
+            return false;
+        }
+        let bucket = id / BUCKET_SIZE;
+        let mut pos =
+            u64::from_le_bytes(self.offsets[bucket * 8..bucket * 8 + 8].try_into().unwrap())
+                as usize;
+        // the length of the current name and of its longest prefix that is
+        // also a prefix of the key
+        let mut len = 0;
+        let mut matched = 0;
+        for i in 0..=id % BUCKET_SIZE {
+            let common = if i == 0 {
+                0
+            } else {
+                read_varint(&self.names, &mut pos)
+            };
+            let rest_len = read_varint(&self.names, &mut pos);
+            let rest = &self.names[pos..pos + rest_len];
+            pos += rest_len;
+            // the previous name already differs from the key before the end
+            // of the shared prefix, and so does this one
+            if common <= matched {
+                matched = common
+                    + rest
+                        .iter()
+                        .zip(&key[common..])
+                        .take_while(|(a, b)| a == b)
+                        .count();
+            }
+            len = common + rest_len;
+        }
+        len == key.len() && matched == key.len()
+    }
+
+    /// The name of the given id, if it is in range.
+    pub fn get(&self, id: usize) -> Option<String> {
+        let mut name = Vec::new();
+        self.get_into(id, &mut name)
+            .then(|| String::from_utf8_lossy(&name).into_owned())
+    }
+
+    /// Iterate on the names in id order.
+    pub fn iter(&self) -> impl Iterator<Item = String> + '_ {
+        let mut pos = 0;
+        let mut name = Vec::new();
+        (0..self.len).map(move |id| {
+            decode(
+                &self.names,
+                &mut pos,
+                id.is_multiple_of(BUCKET_SIZE),
+                &mut name,
+            );
+            String::from_utf8_lossy(&name).into_owned()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir;
+
+    /// Write the names to a store in a new directory and load it.
+    fn store(names: &[&[u8]]) -> Result<(String, NameStore)> {
+        let dir = temp_dir(std::env::temp_dir());
+        let basename = Path::new(&dir).join("vocab");
+        let mut writer = NameStoreWriter::new(&basename)?;
+        for name in names {
+            writer.push(name)?;
+        }
+        let len = writer.finish()?;
+        let store = NameStore::load_with_len(&basename, len)?;
+        Ok((dir, store))
+    }
+
+    #[test]
+    fn test_varint() {
+        for x in [
+            0,
+            1,
+            0x7f,
+            0x80,
+            0x3fff,
+            0x4000,
+            u32::MAX as usize,
+            usize::MAX,
+        ] {
+            let mut buffer = vec![0xff];
+            write_varint(&mut buffer, x);
+            let mut pos = 1;
+            assert_eq!(read_varint(&buffer, &mut pos), x);
+            assert_eq!(pos, buffer.len());
+        }
+        let mut buffer = Vec::new();
+        write_varint(&mut buffer, 300);
+        assert_eq!(buffer, [0xac, 0x02]);
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        // names across three buckets, with long shared prefixes whose
+        // lengths need two bytes as varints
+        let prefix = "NCBITaxon:".repeat(20);
+        let names = (0..2 * BUCKET_SIZE + 3)
+            .map(|i| format!("{}{:03}", prefix, i * 7))
+            .collect::<Vec<_>>();
+        let names = names.iter().map(|name| name.as_bytes()).collect::<Vec<_>>();
+        let (dir, store) = store(&names)?;
+        assert_eq!(store.len(), names.len());
+        assert_eq!(store.offsets.len(), 3 * 8);
+        for (id, name) in names.iter().enumerate() {
+            assert_eq!(store.get(id).unwrap().as_bytes(), *name);
+            assert!(store.matches(id, name));
+        }
+        // the names around the boundaries of the buckets
+        for id in [BUCKET_SIZE - 1, BUCKET_SIZE, 2 * BUCKET_SIZE] {
+            assert!(store.matches(id, names[id]));
+            assert!(!store.matches(id, names[id - 1]));
+        }
+        assert_eq!(
+            store.iter().collect::<Vec<_>>(),
+            names
+                .iter()
+                .map(|name| String::from_utf8_lossy(name))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(store.get(names.len()), None);
+        assert!(!store.matches(names.len(), names[0]));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches() -> Result<()> {
+        let (dir, store) = store(&[b"", b"GO:0001", b"GO:00011", b"GO:0002", b"GO:1", b"GP"])?;
+        assert!(store.matches(0, b""));
+        assert!(!store.matches(0, b"G"));
+        // a prefix of the name, and a name which is a prefix of the key
+        assert!(!store.matches(2, b"GO:0001"));
+        assert!(!store.matches(1, b"GO:00011"));
+        assert!(store.matches(2, b"GO:00011"));
+        // the previous name differs from the key within the shared prefix
+        assert!(!store.matches(3, b"GO:0102"));
+        assert!(store.matches(3, b"GO:0002"));
+        // the previous name matched more of the key than the shared prefix
+        assert!(!store.matches(4, b"GO:0002"));
+        assert!(store.matches(4, b"GO:1"));
+        assert!(!store.matches(5, b"GO"));
+        assert!(store.matches(5, b"GP"));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+//! The canonical node names, shared by the vocabulary and the graph passes.
+//!
+//! Every identifier read from an input file goes through [`node`], or one of
+//! the builders for the identifiers that are just numbers or local ids in
+//! the input files (NCBI taxa, OMA groups, eggNOG groups, STRING aliases),
+//! which add a prefix so that they cannot collide. A name is trimmed, and the
+//! prefix of a CURIE is replaced by its canonical spelling from
+//! [`CURIE_PREFIXES`], so `NCBITAXON:9606`, `ncbitaxon:9606` and
+//! `taxon:9606` are all `NCBITaxon:9606`.
+//!
+//! These are the display names of the nodes; the vocabulary keys are derived
+//! from them with the [`Case`] policy of the vocabulary, so lookups do not
+//! depend on the case of the inputs.
+//!
+//! The `_ref` variants, e.g. [`node_ref`], borrow the name whenever it is
+//! already canonical, and [`Case::with_key`] folds the case on the stack, so
+//! looking up an identifier of the hot files does not allocate.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// The canonical spelling of the CURIE prefixes, each with its aliases. A
+/// prefix matches the canonical one or one of its aliases ignoring case.
+pub const CURIE_PREFIXES: &[(&str, &[&str])] = &[
+    ("NCBITaxon", &["taxon", "NCBI_TaxID", "NCBI_Taxon"]),
+    ("GO", &[]),
+    ("BTO", &[]),
+    ("CL", &[]),
+    ("DOID", &[]),
+    ("FBcv", &[]),
+    ("HP", &[]),
+    ("MP", &[]),
+    ("ZP", &[]),
+    ("WBPhenotype", &[]),
+    ("UBERON", &[]),
+    ("CHEBI", &[]),
+    ("PR", &[]),
+    ("RO", &[]),
+    ("BFO", &[]),
+    ("OMA", &[]),
+    ("EGG", &["eggNOG"]),
+    ("InterPro", &[]),
+    ("Pfam", &[]),
+    ("UniProtKB", &[]),
+    ("RefSeq", &[]),
+    ("ENSEMBL", &[]),
+];
+
+/// Prefixes of the enrichment terms that we keep, i.e. the ontologies for
+/// which we also load the KGX files.
+pub const ENRICHMENT_FILTER: &[&str] = &["BTO", "CL", "DOID", "FBcv", "GO", "HP", "MP", "ZP"];
+
+/// How the vocabulary keys are derived from the node names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Case {
+    /// Keys are uppercased, so lookups ignore case.
+    #[default]
+    Upper,
+    /// Keys are lowercased, so lookups ignore case.
+    Lower,
+    /// Keys are the names, so lookups are case-sensitive.
+    Preserve,
+}
+
+impl Case {
+    /// The vocabulary key of a node name.
+    pub fn key(self, node_name: &str) -> Cow<'_, str> {
+        match self {
+            Case::Upper => Cow::Owned(node_name.to_uppercase()),
+            Case::Lower => Cow::Owned(node_name.to_lowercase()),
+            Case::Preserve => Cow::Borrowed(node_name),
+        }
+    }
+
+    /// Call `f` with the key of a node name. The key of an ASCII name of at
+    /// most [`MAX_STACK_KEY`] bytes is built on the stack, so it is hashed
+    /// and compared without allocating.
+    pub fn with_key<R>(self, node_name: &str, f: impl FnOnce(&str) -> R) -> R {
+        if self == Case::Preserve {
+            return f(node_name);
+        }
+        if !node_name.is_ascii() || node_name.len() > MAX_STACK_KEY {
+            return f(&self.key(node_name));
+        }
+        let mut buffer = [0; MAX_STACK_KEY];
+        let key = &mut buffer[..node_name.len()];
+        key.copy_from_slice(node_name.as_bytes());
+        if self == Case::Upper {
+            key.make_ascii_uppercase();
+        } else {
+            key.make_ascii_lowercase();
+        }
+        // the case of ASCII letters folds to ASCII letters
+        f(std::str::from_utf8(key).unwrap())
+    }
+}
+
+/// The longest name whose key [`Case::with_key`] builds on the stack.
+pub const MAX_STACK_KEY: usize = 128;
+
+/// The canonical spelling of a CURIE prefix, if it is a known one.
+pub fn canonical_prefix(prefix: &str) -> Option<&'static str> {
+    CURIE_PREFIXES
+        .iter()
+        .find(|(canonical, aliases)| {
+            canonical.eq_ignore_ascii_case(prefix)
+                || aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(prefix))
+        })
+        .map(|(canonical, _)| *canonical)
+}
+
+/// The name of a node which is already an identifier, e.g. a STRING protein,
+/// an OMA entry, a UniProt AC or a CURIE from the KGX files.
+pub fn node(name: &str) -> String {
+    node_ref(name).into_owned()
+}
+
+/// Like [`node`], but borrowing the name unless its prefix must be respelled.
+pub fn node_ref(name: &str) -> Cow<'_, str> {
+    let name = name.trim();
+    match name.split_once(':') {
+        Some((prefix, local)) => match canonical_prefix(prefix) {
+            Some(canonical) if canonical != prefix => {
+                Cow::Owned(format!("{}:{}", canonical, local))
+            }
+            _ => Cow::Borrowed(name),
+        },
+        None => Cow::Borrowed(name),
+    }
+}
+
+/// The name of the node of a NCBI taxon id, e.g. `9606` -> `NCBITaxon:9606`.
+pub fn ncbi_taxon(taxon_id: &str) -> String {
+    format!("NCBITaxon:{}", taxon_id.trim())
+}
+
+/// The NCBI taxon id of a STRING protein, i.e. the digits before the first
+/// `.`, e.g. `23.BEL05_00025` -> `23`, or `None` if there are none.
+pub fn string_protein_taxon(protein_id: &str) -> Option<&str> {
+    let (taxon_id, _) = protein_id.trim().split_once('.')?;
+    let digits = !taxon_id.is_empty() && taxon_id.bytes().all(|byte| byte.is_ascii_digit());
+    digits.then_some(taxon_id)
+}
+
+/// The name of the node of an alias of a STRING protein in the given
+/// namespace, e.g. `ENSEMBL` and `ENSG00000141510` ->
+/// `ENSEMBL:ENSG00000141510`. The aliases are kept as they are if the
+/// namespace is empty, e.g. for the UniProt ACs, or if they already have its
+/// prefix, e.g. `HGNC:11998`.
+pub fn string_alias<'a>(namespace: &str, alias: &'a str) -> Cow<'a, str> {
+    let alias = alias.trim();
+    let prefixed = alias
+        .split_once(':')
+        .is_some_and(|(prefix, _)| prefix.eq_ignore_ascii_case(namespace));
+    if namespace.is_empty() || prefixed {
+        return node_ref(alias);
+    }
+    let namespace = canonical_prefix(namespace).unwrap_or(namespace);
+    Cow::Owned(format!("{}:{}", namespace, alias))
+}
+
+/// The name of the node of an OMA group number, e.g. `1` -> `OMA:1`.
+pub fn oma_group(group: &str) -> String {
+    format!("OMA:{}", group.trim())
+}
+
+/// The name of the node of the OMA species of an OMA entry, i.e. its first
+/// five characters, e.g. `HALJB02176` -> `HALJB`, or `None` if the entry is
+/// shorter.
+pub fn oma_species_of_entry(entry: &str) -> Option<&str> {
+    entry.trim().get(..5)
+}
+
+/// The name of the node of an eggNOG orthologous group, e.g. `4R1PH` ->
+/// `EGG:4R1PH`.
+pub fn eggnog_group(group: &str) -> String {
+    format!("EGG:{}", group.trim())
+}
+
+/// The name of the node of a cross-reference of a UniProt entry, e.g.
+/// `InterPro` and `IPR007031` -> `InterPro:IPR007031`. The identifiers that
+/// are already CURIEs, e.g. `GO:0046782`, are kept as they are.
+pub fn uniprot_xref(database: &str, id: &str) -> String {
+    if id.contains(':') {
+        node(id)
+    } else {
+        node(&format!("{}:{}", database, id))
+    }
+}
+
+/// The name of the node of the review status of UniProt entries, i.e.
+/// `UniProtKB:reviewed` or `UniProtKB:unreviewed`.
+pub fn uniprot_status(reviewed: bool) -> &'static str {
+    if reviewed {
+        "UniProtKB:reviewed"
+    } else {
+        "UniProtKB:unreviewed"
+    }
+}
+
+/// The label of the arcs of the enrichment terms of a STRING category, i.e.
+/// the category without its database, in snake case, e.g.
+/// `Biological Process (Gene Ontology)` -> `biological_process`.
+pub fn enrichment_category(category: &str) -> String {
+    let category = category.split('(').next().unwrap_or_default();
+    let mut label = String::with_capacity(category.len());
+    for c in category.trim().chars() {
+        if c.is_alphanumeric() {
+            label.extend(c.to_lowercase());
+        } else if !label.is_empty() && !label.ends_with('_') {
+            label.push('_');
+        }
+    }
+    label.trim_end_matches('_').to_string()
+}
+
+/// Returns the node name of an enrichment term if it belongs to one of the
+/// ontologies in [`ENRICHMENT_FILTER`].
+pub fn enrichment_term(term: &str) -> Option<String> {
+    enrichment_term_ref(term).map(Cow::into_owned)
+}
+
+/// Like [`enrichment_term`], but borrowing the name unless its prefix must
+/// be respelled.
+pub fn enrichment_term_ref(term: &str) -> Option<Cow<'_, str>> {
+    let term = node_ref(term);
+    let (prefix, _) = term.split_once(':')?;
+    ENRICHMENT_FILTER
+        .iter()
+        .any(|term_filter| term_filter.eq_ignore_ascii_case(prefix))
+        .then_some(term)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_key() {
+        let key = |case: Case, name: &str| case.with_key(name, str::to_string);
+        assert_eq!(key(Case::Upper, "NCBITaxon:9606"), "NCBITAXON:9606");
+        assert_eq!(key(Case::Lower, "NCBITaxon:9606"), "ncbitaxon:9606");
+        assert_eq!(key(Case::Preserve, "NCBITaxon:9606"), "NCBITaxon:9606");
+        // the names that are not ASCII or too long fold like Case::key
+        for name in ["Straße", "ǅ", &"aB".repeat(MAX_STACK_KEY)] {
+            for case in [Case::Upper, Case::Lower, Case::Preserve] {
+                assert_eq!(key(case, name), case.key(name));
+            }
+        }
+        assert_eq!(key(Case::Upper, "Straße"), "STRASSE");
+    }
+
+    #[test]
+    fn test_node_ref() {
+        assert!(matches!(
+            node_ref("GO:0006139"),
+            Cow::Borrowed("GO:0006139")
+        ));
+        assert!(matches!(node_ref(" 23.BEL05_00025\n"), Cow::Borrowed(_)));
+        assert!(matches!(node_ref("unknown:1"), Cow::Borrowed("unknown:1")));
+        assert!(matches!(
+            node_ref("taxon:9606"),
+            Cow::Owned(name) if name == "NCBITaxon:9606"
+        ));
+        assert_eq!(node_ref("NCBITAXON:9606"), "NCBITaxon:9606");
+        assert_eq!(node_ref("ncbi_taxid:9606"), "NCBITaxon:9606");
+        assert_eq!(node_ref(" go:0006139 "), "GO:0006139");
+        assert_eq!(node_ref("eggnog:4R1PH"), "EGG:4R1PH");
+        // only the prefix before the first colon is rewritten
+        assert_eq!(node_ref("go:GO:1"), "GO:GO:1");
+    }
+
+    #[test]
+    fn test_string_protein_taxon() {
+        assert_eq!(string_protein_taxon("23.BEL05_00025"), Some("23"));
+        assert_eq!(string_protein_taxon(" 9606.ENSP00000269305 "), Some("9606"));
+        assert_eq!(string_protein_taxon("BEL05_00025"), None);
+        assert_eq!(string_protein_taxon("ab12.BEL05_00025"), None);
+        assert_eq!(string_protein_taxon(".BEL05_00025"), None);
+    }
+
+    #[test]
+    fn test_string_alias() {
+        assert!(matches!(
+            string_alias("", "P69905"),
+            Cow::Borrowed("P69905")
+        ));
+        assert!(matches!(
+            string_alias("HGNC", "HGNC:11998"),
+            Cow::Borrowed("HGNC:11998")
+        ));
+        assert_eq!(
+            string_alias("ENSEMBL", " ENSG00000141510 "),
+            "ENSEMBL:ENSG00000141510"
+        );
+        // the namespace gets its canonical spelling
+        assert_eq!(
+            string_alias("ensembl", "ENSG00000141510"),
+            "ENSEMBL:ENSG00000141510"
+        );
+        assert_eq!(string_alias("go", "go:0006139"), "GO:0006139");
+        assert_eq!(string_alias("Ensembl_gene", "ENSG1"), "Ensembl_gene:ENSG1");
+    }
+
+    #[test]
+    fn test_enrichment_category() {
+        assert_eq!(
+            enrichment_category("Biological Process (Gene Ontology)"),
+            "biological_process"
+        );
+        assert_eq!(
+            enrichment_category("Disease-gene associations (DISEASES)"),
+            "disease_gene_associations"
+        );
+        assert_eq!(
+            enrichment_category("  Tissue expression  "),
+            "tissue_expression"
+        );
+        assert_eq!(
+            enrichment_category("Local network cluster (STRING)"),
+            "local_network_cluster"
+        );
+    }
+
+    #[test]
+    fn test_enrichment_term() {
+        assert!(matches!(
+            enrichment_term_ref("GO:0006139"),
+            Some(Cow::Borrowed("GO:0006139"))
+        ));
+        assert_eq!(enrichment_term("go:0006139").unwrap(), "GO:0006139");
+        assert_eq!(enrichment_term("KW-0001"), None);
+        assert_eq!(enrichment_term("CHEBI:15377"), None);
+    }
+
+    #[test]
+    fn test_oma_species_of_entry() {
+        assert_eq!(oma_species_of_entry("HALJB02176"), Some("HALJB"));
+        assert_eq!(oma_species_of_entry(" HALJB "), Some("HALJB"));
+        assert_eq!(oma_species_of_entry("HALJ"), None);
+        // not on a character boundary
+        assert_eq!(oma_species_of_entry("HALJé1"), None);
+    }
+}
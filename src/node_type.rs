@@ -0,0 +1,110 @@
+//! The kinds of nodes in the graph.
+//!
+//! Every source tells the vocabulary the type of each node name it adds, so
+//! ids can be grouped by type (see [`crate::vocab::IdPolicy`]). The order of
+//! the variants is the order of the groups.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The type of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeType {
+    /// A NCBI taxon, e.g. `NCBITaxon:9606`
+    Taxon,
+    /// A term of one of the other ontologies, e.g. `GO:0006139`
+    OntologyTerm,
+    /// An OMA species code, e.g. `HUMAN`
+    OmaSpecies,
+    /// A STRING protein, e.g. `9606.ENSP00000269305`
+    Protein,
+    /// A UniProt accession, e.g. `P31946`
+    Uniprot,
+    /// An OMA entry, e.g. `HUMAN00001`
+    OmaEntry,
+    /// An OMA group, e.g. `OMA:1`
+    OmaGroup,
+    /// An eggNOG orthologous group, e.g. `EGG:4R1PH`
+    EggnogGroup,
+    /// A protein family or domain, e.g. `InterPro:IPR007031` or
+    /// `Pfam:PF04947`
+    ProteinFamily,
+    /// An annotation shared by many nodes, e.g. `UniProtKB:reviewed`
+    Annotation,
+    /// An alias of a STRING protein in another database, e.g.
+    /// `ENSEMBL:ENSG00000141510`
+    Alias,
+}
+
+impl NodeType {
+    /// All the types, in order.
+    pub const ALL: [NodeType; 11] = [
+        NodeType::Taxon,
+        NodeType::OntologyTerm,
+        NodeType::OmaSpecies,
+        NodeType::Protein,
+        NodeType::Uniprot,
+        NodeType::OmaEntry,
+        NodeType::OmaGroup,
+        NodeType::EggnogGroup,
+        NodeType::ProteinFamily,
+        NodeType::Annotation,
+        NodeType::Alias,
+    ];
+
+    /// The position of the type in [`NodeType::ALL`].
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The type at the given position of [`NodeType::ALL`].
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// The snake case name of the type, as used in the metadata.
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeType::Taxon => "taxon",
+            NodeType::OntologyTerm => "ontology_term",
+            NodeType::OmaSpecies => "oma_species",
+            NodeType::Protein => "protein",
+            NodeType::Uniprot => "uniprot",
+            NodeType::OmaEntry => "oma_entry",
+            NodeType::OmaGroup => "oma_group",
+            NodeType::EggnogGroup => "eggnog_group",
+            NodeType::ProteinFamily => "protein_family",
+            NodeType::Annotation => "annotation",
+            NodeType::Alias => "alias",
+        }
+    }
+
+    /// The type of the node name of an ontology term, which is a taxon if
+    /// it comes from the NCBI taxonomy.
+    pub fn of_term(node_name: &str) -> Self {
+        match node_name.split_once(':') {
+            Some((prefix, _)) if prefix.eq_ignore_ascii_case("NCBITaxon") => NodeType::Taxon,
+            _ => NodeType::OntologyTerm,
+        }
+    }
+}
+
+impl fmt::Display for NodeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for NodeType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::ALL.iter().find(|node_type| node_type.name() == s) {
+            Some(node_type) => Ok(*node_type),
+            None => bail!("Unknown node type {}", s),
+        }
+    }
+}
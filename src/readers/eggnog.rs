@@ -0,0 +1,82 @@
+//! Reader for the eggNOG 6 orthologous groups.
+
+use super::{column, Record, RecordRef};
+use anyhow::{Context, Result};
+
+/// A line of `e6.og2seqs_and_species.tsv`.
+#[derive(Debug, Clone)]
+pub struct EggnogGroup {
+    /// The NCBI taxon id of the last common ancestor of the group
+    pub taxon_id: String,
+    /// The name of the orthologous group, e.g. `4R1PH`
+    pub group: String,
+    /// The number of species in the group
+    pub num_species: usize,
+    /// The number of proteins in the group
+    pub num_proteins: usize,
+    /// The NCBI taxon ids of the species in the group
+    pub species: Vec<String>,
+    /// The STRING ids of the proteins in the group
+    pub proteins: Vec<String>,
+}
+
+/// An [`EggnogGroup`] borrowing its fields from the line.
+#[derive(Debug, Clone, Copy)]
+pub struct EggnogGroupRef<'a> {
+    pub taxon_id: &'a str,
+    pub group: &'a str,
+    pub num_species: usize,
+    pub num_proteins: usize,
+    /// The comma-separated species, see [`EggnogGroupRef::species`]
+    pub species: &'a str,
+    /// The comma-separated proteins, see [`EggnogGroupRef::proteins`]
+    pub proteins: &'a str,
+}
+
+impl<'a> EggnogGroupRef<'a> {
+    /// The NCBI taxon ids of the species in the group.
+    pub fn species(&self) -> impl Iterator<Item = &'a str> {
+        self.species.split(',')
+    }
+
+    /// The STRING ids of the proteins in the group.
+    pub fn proteins(&self) -> impl Iterator<Item = &'a str> {
+        self.proteins.split(',')
+    }
+}
+
+impl From<EggnogGroupRef<'_>> for EggnogGroup {
+    fn from(group: EggnogGroupRef) -> Self {
+        Self {
+            taxon_id: group.taxon_id.to_string(),
+            group: group.group.to_string(),
+            num_species: group.num_species,
+            num_proteins: group.num_proteins,
+            species: group.species().map(str::to_string).collect(),
+            proteins: group.proteins().map(str::to_string).collect(),
+        }
+    }
+}
+
+impl Record for EggnogGroup {
+    fn parse(line: &str) -> Result<Self> {
+        Self::parse_ref(line.as_bytes()).map(Into::into)
+    }
+}
+
+impl RecordRef for EggnogGroup {
+    type Ref<'a> = EggnogGroupRef<'a>;
+
+    fn parse_ref(line: &[u8]) -> Result<EggnogGroupRef<'_>> {
+        let line = std::str::from_utf8(line).context("The line is not UTF-8")?;
+        let mut vals = line.split('\t');
+        Ok(EggnogGroupRef {
+            taxon_id: column(&mut vals, "taxon id")?,
+            group: column(&mut vals, "group")?,
+            num_species: column(&mut vals, "number of species")?.parse()?,
+            num_proteins: column(&mut vals, "number of proteins")?.parse()?,
+            species: column(&mut vals, "species")?,
+            proteins: column(&mut vals, "proteins")?,
+        })
+    }
+}
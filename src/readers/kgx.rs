@@ -0,0 +1,410 @@
+//! Readers for the KGX TSV node and edge lists of the ontologies.
+//!
+//! The KGX exports of kg-hub do not agree on the order of their columns, so
+//! [`KgxRecords`] resolves the columns by the names in the header, and the
+//! record types only say which columns they need. A field can be quoted with
+//! `"`, with `""` standing for a quote, so it can contain tabs; a malformed
+//! quoted field is kept as it is and counted. The multi-valued fields, e.g.
+//! `category` or `synonym`, are separated by `|`.
+//!
+//! The files can also be read straight out of the `*_kgx_tsv.tar.gz`
+//! archives of kg-hub with [`with_kgx_archive_member`].
+
+use super::open_input;
+use anyhow::{bail, ensure, Context, Result};
+use std::collections::HashMap;
+use std::io;
+use std::io::prelude::*;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// The separator of the values of a multi-valued field.
+pub const KGX_MULTI_VALUE_SEPARATOR: char = '|';
+
+/// The columns of a KGX file, by name.
+#[derive(Debug, Clone)]
+pub struct KgxHeader {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl KgxHeader {
+    /// Parse a header line.
+    pub fn parse(line: &str) -> Result<Self> {
+        let (names, malformed) = split_fields(line);
+        ensure!(malformed == 0, "Malformed quoted column name");
+        let mut index = HashMap::new();
+        for (i, name) in names.iter().enumerate() {
+            if index.insert(name.clone(), i).is_some() {
+                bail!("Duplicate column {}", name);
+            }
+        }
+        Ok(Self { names, index })
+    }
+
+    /// The names of the columns, in order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The position of a column, if the file has it.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+}
+
+/// A line of a KGX file, whose fields are accessed by column name.
+pub struct KgxRow<'a> {
+    header: &'a KgxHeader,
+    fields: Vec<String>,
+}
+
+impl KgxRow<'_> {
+    /// The value of a column, or `None` if the file does not have it or the
+    /// field is empty.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.header
+            .position(name)
+            .and_then(|i| self.fields.get(i))
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    /// The value of a column that cannot be missing or empty.
+    pub fn required(&self, name: &str) -> Result<&str> {
+        self.get(name)
+            .with_context(|| format!("Missing column {}", name))
+    }
+
+    /// The values of a multi-valued column, empty if it is missing.
+    pub fn values(&self, name: &str) -> Vec<String> {
+        self.get(name)
+            .map(|value| {
+                value
+                    .split(KGX_MULTI_VALUE_SEPARATOR)
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Split a line on tabs, removing the quotes around the quoted fields. A
+/// malformed quoted field, e.g. `"foo" bar`, is kept as it is up to the next
+/// tab. Returns the fields and how many of them were malformed.
+fn split_fields(line: &str) -> (Vec<String>, usize) {
+    let mut fields = Vec::new();
+    let mut malformed = 0;
+    let mut rest = line;
+    loop {
+        let (field, next) = match unquote(rest) {
+            Some(quoted) => quoted,
+            None => {
+                if rest.starts_with('"') {
+                    malformed += 1;
+                }
+                match rest.split_once('\t') {
+                    Some((field, next)) => (field.to_string(), Some(next)),
+                    None => (rest.to_string(), None),
+                }
+            }
+        };
+        fields.push(field);
+        match next {
+            Some(next) => rest = next,
+            None => return (fields, malformed),
+        }
+    }
+}
+
+/// The quoted field at the start of `text` without its quotes, and the text
+/// after the tab that follows it, or `None` if `text` does not start with a
+/// well-formed quoted field.
+fn unquote(text: &str) -> Option<(String, Option<&str>)> {
+    let quoted = text.strip_prefix('"')?;
+    let mut field = String::new();
+    let mut chars = quoted.char_indices().peekable();
+    loop {
+        match chars.next()? {
+            (_, '"') if chars.peek().is_some_and(|(_, c)| *c == '"') => {
+                chars.next();
+                field.push('"');
+            }
+            (end, '"') => {
+                let after = &quoted[end + 1..];
+                return match after.strip_prefix('\t') {
+                    Some(next) => Some((field, Some(next))),
+                    None if after.is_empty() => Some((field, None)),
+                    None => None,
+                };
+            }
+            (_, c) => field.push(c),
+        }
+    }
+}
+
+/// A record that can be built from a line of a KGX file.
+pub trait KgxRecord: Sized {
+    /// The columns that the header must have.
+    const REQUIRED_COLUMNS: &'static [&'static str];
+
+    /// Build a record from a line.
+    fn from_row(row: &KgxRow) -> Result<Self>;
+}
+
+/// How many of the line numbers of [`MalformedLines`] are kept.
+pub const NUM_MALFORMED_SAMPLES: usize = 10;
+
+/// The lines of a KGX file with a malformed quoted field, which is kept as it
+/// is, see [`KgxRecords::malformed`].
+#[derive(Debug, Clone)]
+pub struct MalformedLines {
+    /// The file
+    pub path: PathBuf,
+    /// How many lines had a malformed quoted field
+    pub count: usize,
+    /// The numbers of the first [`NUM_MALFORMED_SAMPLES`] of them
+    pub samples: Vec<usize>,
+}
+
+/// Iterator over the records of a KGX file. The lines with a malformed quoted
+/// field, which is kept as it is, are counted in [`KgxRecords::malformed`].
+pub struct KgxRecords<'a, T: KgxRecord> {
+    lines: io::Lines<Box<dyn BufRead + 'a>>,
+    line_number: usize,
+    header: KgxHeader,
+    malformed: MalformedLines,
+    _marker: PhantomData<T>,
+}
+
+impl<T: KgxRecord> KgxRecords<'static, T> {
+    /// Open the file at the given path and read its header.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        Self::new(open_input(&path)?, path)
+    }
+}
+
+impl<'a, T: KgxRecord> KgxRecords<'a, T> {
+    /// Read the header of a KGX file from `reader`. The path is only used in
+    /// the error messages.
+    pub fn new<P: Into<PathBuf>>(reader: Box<dyn BufRead + 'a>, path: P) -> Result<Self> {
+        let path = path.into();
+        let mut lines = reader.lines();
+        let line = lines
+            .next()
+            .with_context(|| format!("{} has no header", path.display()))??;
+        let header = KgxHeader::parse(&line)
+            .with_context(|| format!("Unexpected header in {}", path.display()))?;
+        for name in T::REQUIRED_COLUMNS {
+            if header.position(name).is_none() {
+                bail!(
+                    "Unexpected header in {}: missing column {}, got {:?}",
+                    path.display(),
+                    name,
+                    header.names()
+                );
+            }
+        }
+        Ok(Self {
+            lines,
+            line_number: 1,
+            header,
+            malformed: MalformedLines {
+                path,
+                count: 0,
+                samples: Vec::new(),
+            },
+            _marker: PhantomData,
+        })
+    }
+
+    /// The path of the file being read.
+    pub fn path(&self) -> &Path {
+        &self.malformed.path
+    }
+
+    /// The lines with a malformed quoted field read so far, or `None` if
+    /// there are none.
+    pub fn malformed(&self) -> Option<&MalformedLines> {
+        (self.malformed.count > 0).then_some(&self.malformed)
+    }
+
+    /// The header of the file.
+    pub fn header(&self) -> &KgxHeader {
+        &self.header
+    }
+
+    fn parse(&mut self, line: &str) -> Result<T> {
+        let (fields, malformed) = split_fields(line);
+        if malformed > 0 {
+            if self.malformed.samples.len() < NUM_MALFORMED_SAMPLES {
+                self.malformed.samples.push(self.line_number);
+            }
+            self.malformed.count += 1;
+        }
+        T::from_row(&KgxRow {
+            header: &self.header,
+            fields,
+        })
+    }
+}
+
+impl<T: KgxRecord> Iterator for KgxRecords<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?;
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+            self.line_number += 1;
+            if line.is_empty() {
+                continue;
+            }
+            return Some(self.parse(&line).with_context(|| {
+                format!(
+                    "Could not parse {}:{}",
+                    self.path().display(),
+                    self.line_number
+                )
+            }));
+        }
+    }
+}
+
+/// Call `f` on the records of the member of a KGX archive, e.g.
+/// `go_kgx_tsv.tar.gz`, whose file name ends with `suffix`, e.g.
+/// `_nodes.tsv`. The member is decompressed while it is read, so the archive
+/// is never extracted to disk.
+pub fn with_kgx_archive_member<T: KgxRecord, R>(
+    path: &Path,
+    suffix: &str,
+    f: impl FnOnce(KgxRecords<'_, T>) -> Result<R>,
+) -> Result<R> {
+    let mut archive = tar::Archive::new(open_input(path)?);
+    for entry in archive
+        .entries()
+        .with_context(|| format!("Could not read {}", path.display()))?
+    {
+        let entry = entry.with_context(|| format!("Could not read {}", path.display()))?;
+        let member = entry.path()?.into_owned();
+        let is_match = member
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().ends_with(suffix));
+        if entry.header().entry_type().is_file() && is_match {
+            let records = KgxRecords::new(Box::new(io::BufReader::new(entry)), path.join(&member))?;
+            return f(records);
+        }
+    }
+    bail!("No file ending in {} in {}", suffix, path.display())
+}
+
+/// A line of a `*_kgx_tsv_nodes.tsv` file.
+#[derive(Debug, Clone)]
+pub struct KgxNode {
+    /// The CURIE of the node, e.g. `GO:0006139`
+    pub id: String,
+    /// The biolink categories, e.g. `biolink:NamedThing`
+    pub category: Vec<String>,
+    /// The label of the node, e.g. `nucleobase-containing compound metabolic
+    /// process`
+    pub name: Option<String>,
+    /// The definition of the node
+    pub description: Option<String>,
+    /// The synonyms of the node
+    pub synonym: Vec<String>,
+    /// The CURIEs of the same node in other ontologies
+    pub xref: Vec<String>,
+}
+
+impl KgxRecord for KgxNode {
+    const REQUIRED_COLUMNS: &'static [&'static str] = &["id"];
+
+    fn from_row(row: &KgxRow) -> Result<Self> {
+        Ok(Self {
+            id: row.required("id")?.to_string(),
+            category: row.values("category"),
+            name: row.get("name").map(str::to_string),
+            description: row.get("description").map(str::to_string),
+            synonym: row.values("synonym"),
+            xref: row.values("xref"),
+        })
+    }
+}
+
+/// A line of a `*_kgx_tsv_edges.tsv` file.
+#[derive(Debug, Clone)]
+pub struct KgxEdge {
+    /// The CURIE of the source node
+    pub subject: String,
+    /// The predicate, e.g. `biolink:subclass_of`
+    pub predicate: String,
+    /// The CURIE of the destination node
+    pub object: String,
+    /// The relation the predicate was derived from, e.g. `rdfs:subClassOf`
+    /// or `BFO:0000050`, if the file has this column
+    pub relation: Option<String>,
+}
+
+impl KgxRecord for KgxEdge {
+    const REQUIRED_COLUMNS: &'static [&'static str] = &["subject", "predicate", "object"];
+
+    fn from_row(row: &KgxRow) -> Result<Self> {
+        Ok(Self {
+            subject: row.required("subject")?.to_string(),
+            predicate: row.required("predicate")?.to_string(),
+            object: row.required("object")?.to_string(),
+            relation: row.get("relation").map(str::to_string),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_tab() {
+        let (fields, malformed) = split_fields("GO:1\t\"a\tb\"\tc");
+        assert_eq!(fields, ["GO:1", "a\tb", "c"]);
+        assert_eq!(malformed, 0);
+        assert_eq!(unquote("\"a\tb\""), Some(("a\tb".to_string(), None)));
+    }
+
+    #[test]
+    fn test_escaped_quote() {
+        let (fields, malformed) = split_fields("\"say \"\"hi\"\"\"\t\"\"");
+        assert_eq!(fields, ["say \"hi\"", ""]);
+        assert_eq!(malformed, 0);
+    }
+
+    #[test]
+    fn test_malformed_quote() {
+        // unterminated, or followed by more text before the tab
+        assert_eq!(unquote("\"a\tb"), None);
+        assert_eq!(unquote("\"a\" b\tc"), None);
+        let (fields, malformed) = split_fields("\"a\" b\tc\t\"d");
+        assert_eq!(fields, ["\"a\" b", "c", "\"d"]);
+        assert_eq!(malformed, 2);
+    }
+
+    #[test]
+    fn test_malformed_lines() -> Result<()> {
+        let text = "id\tname\nGO:1\t\"a\" b\nGO:2\t\"b\"\n\nGO:3\t\"c\n";
+        let mut nodes = KgxRecords::<KgxNode>::new(Box::new(text.as_bytes()), "nodes.tsv")?;
+        assert!(nodes.malformed().is_none());
+        let names = (&mut nodes)
+            .map(|node| Ok(node?.name.unwrap()))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(names, ["\"a\" b", "b", "\"c"]);
+        let malformed = nodes.malformed().unwrap();
+        assert_eq!(malformed.count, 2);
+        assert_eq!(malformed.samples, [2, 5]);
+        Ok(())
+    }
+}
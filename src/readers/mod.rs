@@ -0,0 +1,337 @@
+//! One reader per input file, each yielding typed records.
+//!
+//! All the readers skip the lines starting with `#` and the header lines
+//! declared by the record type, and open the files with [`open_input`], so
+//! any input can be stored plain or compressed with gzip, zstd or xz,
+//! whatever its extension.
+//! The records of the huge files also have a borrowed form, see
+//! [`RecordRef`], that [`Records::try_for_each_ref`] and [`par_for_each`]
+//! parse from a reused line buffer without allocating. The KGX files have
+//! their own reader, [`KgxRecords`], which finds the columns by name, and
+//! the UniProtKB flat files, whose entries span many lines, have
+//! [`UniprotEntries`].
+
+use anyhow::{bail, Context, Result};
+use flate2::read::MultiGzDecoder;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+mod eggnog;
+mod kgx;
+mod oma;
+mod parallel;
+mod string;
+mod uniprot;
+
+pub use eggnog::*;
+pub use kgx::*;
+pub use oma::*;
+pub use parallel::*;
+pub use string::*;
+pub use uniprot::*;
+
+/// A record that can be parsed from a line of an input file.
+pub trait Record: Sized {
+    /// How many lines not starting with `#` must be skipped at the start of
+    /// the file.
+    const HEADER_LINES: usize = 0;
+
+    /// Check that a header line has the expected columns.
+    fn check_header(_header: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Parse a single line, without the trailing newline.
+    fn parse(line: &str) -> Result<Self>;
+}
+
+/// A [`Record`] that can also be parsed into a form borrowing its fields from
+/// the line, so that parsing it does not allocate.
+pub trait RecordRef: Record {
+    /// The record, borrowing its fields from the line
+    type Ref<'a>;
+
+    /// Parse a single line, without the trailing newline.
+    fn parse_ref(line: &[u8]) -> Result<Self::Ref<'_>>;
+}
+
+/// The compression formats recognized by [`open_input`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// Gzip, including multi-member files such as the bgzip ones
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    /// The compression of a file starting with the given bytes.
+    pub fn sniff(magic: &[u8]) -> Self {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Open a file, decompressing it according to its first bytes rather than
+/// to its extension.
+pub fn open_input<P: AsRef<Path>>(path: P) -> Result<Box<dyn BufRead>> {
+    let path = path.as_ref();
+    let file =
+        fs::File::open(path).with_context(|| format!("Could not open {}", path.display()))?;
+    let mut file = io::BufReader::new(file);
+    let compression = Compression::sniff(
+        file.fill_buf()
+            .with_context(|| format!("Could not read {}", path.display()))?,
+    );
+    Ok(match compression {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(io::BufReader::new(MultiGzDecoder::new(file))),
+        Compression::Zstd => Box::new(io::BufReader::new(
+            zstd::Decoder::with_buffer(file)
+                .with_context(|| format!("Could not read {}", path.display()))?,
+        )),
+        Compression::Xz => Box::new(io::BufReader::new(
+            xz2::bufread::XzDecoder::new_multi_decoder(file),
+        )),
+    })
+}
+
+/// Skip the header of a file, checking it, and return the number of lines
+/// read.
+pub(crate) fn skip_header<T: Record>(reader: &mut dyn BufRead, path: &Path) -> Result<usize> {
+    let mut line_number = 0;
+    let mut to_skip = T::HEADER_LINES;
+    let mut line = String::new();
+    while to_skip > 0 {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            bail!("{} has no header", path.display());
+        }
+        line_number += 1;
+        if !line.starts_with('#') {
+            T::check_header(line.trim_end_matches(['\n', '\r']))
+                .with_context(|| format!("Unexpected header in {}", path.display()))?;
+            to_skip -= 1;
+        }
+    }
+    Ok(line_number)
+}
+
+/// Iterator over the records of a file.
+pub struct Records<T: Record> {
+    path: PathBuf,
+    reader: Box<dyn BufRead>,
+    /// The current line, reused to read all of them
+    line: Vec<u8>,
+    line_number: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Record> Records<T> {
+    /// Open the file at the given path and skip its header.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let mut reader = open_input(&path)?;
+        let line_number = skip_header::<T>(&mut reader, &path)?;
+        Ok(Self {
+            path,
+            reader,
+            line: Vec::new(),
+            line_number,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The path of the file being read.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the next line that is not a comment into `self.line`, returning
+    /// false at the end of the file.
+    fn next_line(&mut self) -> Result<bool> {
+        loop {
+            self.line.clear();
+            if self
+                .reader
+                .read_until(b'\n', &mut self.line)
+                .with_context(|| format!("Could not read {}", self.path.display()))?
+                == 0
+            {
+                return Ok(false);
+            }
+            self.line_number += 1;
+            if self.line.ends_with(b"\n") {
+                self.line.pop();
+                if self.line.ends_with(b"\r") {
+                    self.line.pop();
+                }
+            }
+            if !self.line.starts_with(b"#") {
+                return Ok(true);
+            }
+        }
+    }
+
+    fn context(&self) -> String {
+        format!(
+            "Could not parse {}:{}",
+            self.path.display(),
+            self.line_number
+        )
+    }
+}
+
+impl<T: RecordRef> Records<T> {
+    /// Call `f` on the borrowed form of every record, stopping at the first
+    /// error. Unlike iterating, this does not allocate for each line.
+    pub fn try_for_each_ref(mut self, mut f: impl FnMut(T::Ref<'_>) -> Result<()>) -> Result<()> {
+        while self.next_line()? {
+            let record = T::parse_ref(&self.line).with_context(|| self.context())?;
+            f(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Record> Iterator for Records<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_line() {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(err) => return Some(Err(err)),
+        }
+        Some(
+            std::str::from_utf8(&self.line)
+                .map_err(Into::into)
+                .and_then(T::parse)
+                .with_context(|| self.context()),
+        )
+    }
+}
+
+/// Get the next column of a line, or an error mentioning its name.
+pub(crate) fn column<'a>(vals: &mut impl Iterator<Item = &'a str>, name: &str) -> Result<&'a str> {
+    vals.next()
+        .with_context(|| format!("Missing column {}", name))
+}
+
+/// Get the next column of a line split into bytes, as a `&str`.
+pub(crate) fn str_column<'a>(
+    vals: &mut impl Iterator<Item = &'a [u8]>,
+    name: &str,
+) -> Result<&'a str> {
+    let val = vals
+        .next()
+        .with_context(|| format!("Missing column {}", name))?;
+    std::str::from_utf8(val).with_context(|| format!("Column {} is not UTF-8", name))
+}
+
+/// Parse a decimal `u16` from its digits, without going through a `&str`.
+pub(crate) fn parse_u16(digits: &[u8]) -> Result<u16> {
+    if digits.is_empty() || digits.len() > 5 {
+        bail!("Invalid number {:?}", String::from_utf8_lossy(digits));
+    }
+    let mut value = 0u32;
+    for &digit in digits {
+        if !digit.is_ascii_digit() {
+            bail!("Invalid number {:?}", String::from_utf8_lossy(digits));
+        }
+        value = value * 10 + (digit - b'0') as u32;
+    }
+    u16::try_from(value).with_context(|| format!("Number {} out of range", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir;
+    use flate2::write::GzEncoder;
+
+    const TEXT: &str = "9606.ENSP00000000233\t9606.ENSP00000272298\t490\n";
+
+    /// Write `data` to a file in a new directory and read it back with
+    /// [`open_input`].
+    fn read_back(data: &[u8]) -> Result<String> {
+        let dir = temp_dir(std::env::temp_dir());
+        let path = Path::new(&dir).join("input.txt");
+        fs::write(&path, data)?;
+        let mut text = String::new();
+        open_input(&path)?.read_to_string(&mut text)?;
+        fs::remove_dir_all(&dir)?;
+        Ok(text)
+    }
+
+    fn gzip(text: &str) -> Result<Vec<u8>> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes())?;
+        Ok(encoder.finish()?)
+    }
+
+    #[test]
+    fn test_sniff() -> Result<()> {
+        assert_eq!(Compression::sniff(&gzip(TEXT)?), Compression::Gzip);
+        assert_eq!(
+            Compression::sniff(&zstd::encode_all(TEXT.as_bytes(), 0)?),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::sniff(&[0xfd, b'7', b'z', b'X', b'Z', 0x00, 0x00]),
+            Compression::Xz
+        );
+        assert_eq!(Compression::sniff(TEXT.as_bytes()), Compression::None);
+        // shorter than the magic bytes
+        assert_eq!(Compression::sniff(&[0x1f]), Compression::None);
+        assert_eq!(Compression::sniff(&[0xfd, b'7']), Compression::None);
+        assert_eq!(Compression::sniff(&[]), Compression::None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_input() -> Result<()> {
+        assert_eq!(read_back(TEXT.as_bytes())?, TEXT);
+        assert_eq!(read_back(&gzip(TEXT)?)?, TEXT);
+        assert_eq!(read_back(&zstd::encode_all(TEXT.as_bytes(), 0)?)?, TEXT);
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(TEXT.as_bytes())?;
+        assert_eq!(read_back(&encoder.finish()?)?, TEXT);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_member_gzip() -> Result<()> {
+        // e.g. bgzip, or files concatenated with cat
+        let mut data = gzip(TEXT)?;
+        data.extend(gzip("second member\n")?);
+        assert_eq!(read_back(&data)?, format!("{}second member\n", TEXT));
+        Ok(())
+    }
+
+    #[test]
+    fn test_short_input() -> Result<()> {
+        assert_eq!(read_back(b"")?, "");
+        assert_eq!(read_back(b"x")?, "x");
+        // the first byte of the gzip magic only
+        let dir = temp_dir(std::env::temp_dir());
+        let path = Path::new(&dir).join("input.txt");
+        fs::write(&path, [0x1f])?;
+        let mut data = Vec::new();
+        open_input(&path)?.read_to_end(&mut data)?;
+        assert_eq!(data, [0x1f]);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
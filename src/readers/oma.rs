@@ -0,0 +1,135 @@
+//! Readers for the OMA browser files.
+
+use super::{column, str_column, Record, RecordRef};
+use anyhow::{Context, Result};
+
+/// A line of `oma-groups.txt.gz`: an orthologous group with the OMA entries
+/// that belong to it.
+#[derive(Debug, Clone)]
+pub struct OmaGroup {
+    /// The group number
+    pub group: String,
+    /// The fingerprint of the group
+    pub fingerprint: String,
+    /// The OMA entry ids in the group, e.g. `HALJB02176`
+    pub entries: Vec<String>,
+}
+
+/// An [`OmaGroup`] borrowing its fields from the line.
+#[derive(Debug, Clone, Copy)]
+pub struct OmaGroupRef<'a> {
+    pub group: &'a str,
+    pub fingerprint: &'a str,
+    /// The tab-separated entries, see [`OmaGroupRef::entries`]
+    pub entries: &'a str,
+}
+
+impl<'a> OmaGroupRef<'a> {
+    /// The OMA entry ids in the group.
+    pub fn entries(&self) -> impl Iterator<Item = &'a str> {
+        self.entries.split('\t').filter(|entry| !entry.is_empty())
+    }
+}
+
+impl From<OmaGroupRef<'_>> for OmaGroup {
+    fn from(group: OmaGroupRef) -> Self {
+        Self {
+            group: group.group.to_string(),
+            fingerprint: group.fingerprint.to_string(),
+            entries: group.entries().map(str::to_string).collect(),
+        }
+    }
+}
+
+impl Record for OmaGroup {
+    fn parse(line: &str) -> Result<Self> {
+        Self::parse_ref(line.as_bytes()).map(Into::into)
+    }
+}
+
+impl RecordRef for OmaGroup {
+    type Ref<'a> = OmaGroupRef<'a>;
+
+    fn parse_ref(line: &[u8]) -> Result<OmaGroupRef<'_>> {
+        let line = std::str::from_utf8(line).context("The line is not UTF-8")?;
+        let mut vals = line.splitn(3, '\t');
+        Ok(OmaGroupRef {
+            group: column(&mut vals, "group")?,
+            fingerprint: column(&mut vals, "fingerprint")?,
+            entries: vals.next().unwrap_or_default(),
+        })
+    }
+}
+
+/// A line of `oma-species.txt`: the mapping of an OMA species code to its
+/// NCBI taxon.
+#[derive(Debug, Clone)]
+pub struct OmaSpecies {
+    /// The OMA species code, e.g. `HALJB`
+    pub code: String,
+    /// The OMA taxon id
+    pub oma_taxon_id: String,
+    /// The NCBI taxon id, e.g. `9606`
+    pub ncbi_taxon_id: String,
+    /// The GTDB genome accession, `n/a` if missing
+    pub gtdb_accession: String,
+    /// The scientific name of the species
+    pub scientific_name: String,
+}
+
+impl Record for OmaSpecies {
+    fn parse(line: &str) -> Result<Self> {
+        let mut vals = line.split('\t');
+        Ok(Self {
+            code: column(&mut vals, "OMA code")?.to_string(),
+            oma_taxon_id: column(&mut vals, "OMA taxon id")?.to_string(),
+            ncbi_taxon_id: column(&mut vals, "NCBI taxon id")?.to_string(),
+            gtdb_accession: column(&mut vals, "GTDB genome accession")?.to_string(),
+            scientific_name: column(&mut vals, "scientific name")?.to_string(),
+        })
+    }
+}
+
+/// A line of `oma-uniprot.txt.gz`: the mapping of an OMA entry to a UniProt
+/// id.
+#[derive(Debug, Clone)]
+pub struct OmaUniprot {
+    /// The OMA entry id, e.g. `ARCFU00001`
+    pub oma_id: String,
+    /// The UniProt id, e.g. `O30234`
+    pub uniprot_id: String,
+}
+
+/// An [`OmaUniprot`] borrowing its fields from the line.
+#[derive(Debug, Clone, Copy)]
+pub struct OmaUniprotRef<'a> {
+    pub oma_id: &'a str,
+    pub uniprot_id: &'a str,
+}
+
+impl From<OmaUniprotRef<'_>> for OmaUniprot {
+    fn from(mapping: OmaUniprotRef) -> Self {
+        Self {
+            oma_id: mapping.oma_id.to_string(),
+            uniprot_id: mapping.uniprot_id.to_string(),
+        }
+    }
+}
+
+impl Record for OmaUniprot {
+    fn parse(line: &str) -> Result<Self> {
+        Self::parse_ref(line.as_bytes()).map(Into::into)
+    }
+}
+
+impl RecordRef for OmaUniprot {
+    type Ref<'a> = OmaUniprotRef<'a>;
+
+    fn parse_ref(line: &[u8]) -> Result<OmaUniprotRef<'_>> {
+        let mut vals = line.split(|&byte| byte == b'\t');
+        Ok(OmaUniprotRef {
+            oma_id: str_column(&mut vals, "OMA id")?,
+            uniprot_id: str_column(&mut vals, "UniProt id")?,
+        })
+    }
+}
@@ -0,0 +1,312 @@
+//! A pipelined reader for the huge inputs, e.g. the STRING links.
+//!
+//! One thread decompresses the file and cuts it into chunks of whole lines,
+//! which a pool of workers parses, each calling back with its own state, so
+//! that e.g. every worker can push arcs into its own sorter.
+
+use super::{open_input, skip_header, RecordRef};
+use anyhow::{anyhow, ensure, Context, Result};
+use std::io::prelude::*;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+/// How many bytes of whole lines each chunk holds, at least.
+pub const CHUNK_SIZE: usize = 8 << 20;
+
+/// A piece of the file made of whole lines.
+struct Chunk {
+    /// The line number of the first line of the chunk
+    first_line: usize,
+    data: Vec<u8>,
+}
+
+/// Parse the records of a file with one thread for each element of
+/// `workers`, calling `f` on the borrowed form of every record with the
+/// state of the worker that parsed it.
+///
+/// The records are parsed in no particular order, so `f` must not depend on
+/// it. The first error of any thread stops the reading and is returned, and
+/// so does a panic of a worker.
+pub fn par_for_each<T, W, F>(path: &Path, workers: &mut [W], f: F) -> Result<()>
+where
+    T: RecordRef,
+    W: Send,
+    F: Fn(&mut W, T::Ref<'_>) -> Result<()> + Sync,
+{
+    par_for_each_chunk::<T, W, F>(path, workers, f, CHUNK_SIZE)
+}
+
+/// Drops the receiver of the chunks when the last worker exits, even by
+/// panicking, so that the reader is never left blocked on a full channel.
+struct WorkerGuard<'a> {
+    receiver: &'a Mutex<Option<mpsc::Receiver<Chunk>>>,
+    live_workers: &'a AtomicUsize,
+    failed: &'a AtomicBool,
+}
+
+impl Drop for WorkerGuard<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.failed.store(true, Ordering::Relaxed);
+        }
+        if self.live_workers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.receiver
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+        }
+    }
+}
+
+/// [`par_for_each`] with chunks of at least `chunk_size` bytes.
+fn par_for_each_chunk<T, W, F>(
+    path: &Path,
+    workers: &mut [W],
+    f: F,
+    chunk_size: usize,
+) -> Result<()>
+where
+    T: RecordRef,
+    W: Send,
+    F: Fn(&mut W, T::Ref<'_>) -> Result<()> + Sync,
+{
+    ensure!(!workers.is_empty(), "No worker to parse {}", path.display());
+    let mut reader = open_input(path)?;
+    let header_lines = skip_header::<T>(&mut reader, path)?;
+    // a few chunks per worker, so that they do not starve while the next
+    // chunk is being decompressed
+    let (sender, receiver) = mpsc::sync_channel::<Chunk>(2 * workers.len());
+    let receiver = Mutex::new(Some(receiver));
+    let live_workers = AtomicUsize::new(workers.len());
+    let failed = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let failed = &failed;
+        let receiver = &receiver;
+        let live_workers = &live_workers;
+        let f = &f;
+        let workers = workers
+            .iter_mut()
+            .map(|worker| {
+                scope.spawn(move || -> Result<()> {
+                    let _guard = WorkerGuard {
+                        receiver,
+                        live_workers,
+                        failed,
+                    };
+                    let mut result = Ok(());
+                    loop {
+                        let chunk = match receiver.lock() {
+                            Ok(receiver) => receiver.as_ref().and_then(|r| r.recv().ok()),
+                            Err(_) => {
+                                failed.store(true, Ordering::Relaxed);
+                                return result.and(Err(anyhow!(
+                                    "A worker parsing {} panicked",
+                                    path.display()
+                                )));
+                            }
+                        };
+                        let Some(chunk) = chunk else {
+                            return result;
+                        };
+                        // after an error keep draining the chunks, so the
+                        // reader does not block
+                        if failed.load(Ordering::Relaxed) {
+                            continue;
+                        }
+                        if let Err(err) = parse_chunk(path, &chunk, worker, f) {
+                            failed.store(true, Ordering::Relaxed);
+                            result = Err(err);
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut line_number = header_lines;
+        let read = (|| -> Result<()> {
+            while !failed.load(Ordering::Relaxed) {
+                let mut data = Vec::with_capacity(chunk_size + (1 << 16));
+                (&mut reader)
+                    .take(chunk_size as u64)
+                    .read_to_end(&mut data)
+                    .with_context(|| format!("Could not read {}", path.display()))?;
+                if data.is_empty() {
+                    break;
+                }
+                if data.last() != Some(&b'\n') {
+                    reader
+                        .read_until(b'\n', &mut data)
+                        .with_context(|| format!("Could not read {}", path.display()))?;
+                }
+                let first_line = line_number + 1;
+                line_number += data.iter().filter(|&&byte| byte == b'\n').count();
+                if sender.send(Chunk { first_line, data }).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        })();
+        if read.is_err() {
+            failed.store(true, Ordering::Relaxed);
+        }
+        drop(sender);
+
+        let mut result = read;
+        for worker in workers {
+            let worker_result = worker
+                .join()
+                .unwrap_or_else(|_| Err(anyhow!("A worker parsing {} panicked", path.display())));
+            if result.is_ok() {
+                result = worker_result;
+            }
+        }
+        result
+    })
+}
+
+/// Parse the lines of a chunk, skipping the comments as [`super::Records`]
+/// does.
+fn parse_chunk<T: RecordRef, W>(
+    path: &Path,
+    chunk: &Chunk,
+    worker: &mut W,
+    f: &impl Fn(&mut W, T::Ref<'_>) -> Result<()>,
+) -> Result<()> {
+    let data = chunk.data.strip_suffix(b"\n").unwrap_or(&chunk.data);
+    for (i, line) in data.split(|&byte| byte == b'\n').enumerate() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.starts_with(b"#") {
+            continue;
+        }
+        let context = || {
+            format!(
+                "Could not parse {}:{}",
+                path.display(),
+                chunk.first_line + i
+            )
+        };
+        f(worker, T::parse_ref(line).with_context(context)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::readers::Record;
+    use crate::utils::temp_dir;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A number on each line, after a header line.
+    struct Number;
+
+    impl Record for Number {
+        const HEADER_LINES: usize = 1;
+
+        fn parse(_line: &str) -> Result<Self> {
+            unimplemented!()
+        }
+    }
+
+    impl RecordRef for Number {
+        type Ref<'a> = u64;
+
+        fn parse_ref(line: &[u8]) -> Result<u64> {
+            Ok(std::str::from_utf8(line)?.parse()?)
+        }
+    }
+
+    /// Write a header and the given lines to a file in a new directory.
+    fn write_lines(lines: impl Iterator<Item = String>) -> (String, PathBuf) {
+        let dir = temp_dir(std::env::temp_dir());
+        let path = Path::new(&dir).join("numbers.tsv");
+        let mut text = "number\n".to_string();
+        for line in lines {
+            text.push_str(&line);
+            text.push('\n');
+        }
+        fs::write(&path, text).unwrap();
+        (dir, path)
+    }
+
+    /// The sorted numbers parsed by the given number of workers.
+    fn numbers(path: &Path, num_workers: usize) -> Result<Vec<u64>> {
+        let mut workers = vec![Vec::new(); num_workers];
+        par_for_each_chunk::<Number, _, _>(
+            path,
+            &mut workers,
+            |numbers, number| {
+                numbers.push(number);
+                Ok(())
+            },
+            16,
+        )?;
+        let mut numbers = workers.concat();
+        numbers.sort();
+        Ok(numbers)
+    }
+
+    #[test]
+    fn test_num_workers() -> Result<()> {
+        let (dir, path) = write_lines((0..1000).map(|i| i.to_string()));
+        let expected = (0..1000).collect::<Vec<_>>();
+        assert_eq!(numbers(&path, 1)?, expected);
+        assert_eq!(numbers(&path, 4)?, expected);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_number() -> Result<()> {
+        let (dir, path) = write_lines((0..1000).map(|i| match i {
+            800 => "x".to_string(),
+            i => i.to_string(),
+        }));
+        let err = numbers(&path, 4).unwrap_err();
+        // the header is the first line
+        let expected = format!("Could not parse {}:802", path.display());
+        assert_eq!(err.to_string(), expected);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_callback_error() -> Result<()> {
+        let (dir, path) = write_lines((0..100_000).map(|i| i.to_string()));
+        let calls = AtomicUsize::new(0);
+        let result = par_for_each_chunk::<Number, _, _>(
+            &path,
+            &mut [()],
+            |_, number| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                anyhow::ensure!(number != 0, "Zero");
+                Ok(())
+            },
+            16,
+        );
+        assert_eq!(result.unwrap_err().to_string(), "Zero");
+        // the chunks read after the error are not parsed
+        assert_eq!(calls.into_inner(), 1);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_worker_panic() -> Result<()> {
+        let (dir, path) = write_lines((0..100_000).map(|i| i.to_string()));
+        let result = par_for_each_chunk::<Number, _, _>(
+            &path,
+            &mut [(), ()],
+            |_, _| panic!("Parsing failed"),
+            16,
+        );
+        assert!(result.unwrap_err().to_string().contains("panicked"));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+//! Readers for the STRING files.
+//!
+//! The info, aliases, enrichment terms and links, which have up to billions
+//! of lines, also have a borrowed form, e.g. [`StringLinkRef`], which the owned one is
+//! parsed through.
+
+use super::{parse_u16, str_column, Record, RecordRef};
+use anyhow::{ensure, Context, Result};
+
+/// A line of `protein.info.v12.0.txt.gz`.
+#[derive(Debug, Clone)]
+pub struct StringInfo {
+    /// The STRING protein id, e.g. `23.BEL05_00025`
+    pub protein_id: String,
+    /// The preferred name of the protein, e.g. `acsA`
+    pub preferred_name: String,
+    /// The length of the protein in amino acids
+    pub protein_size: usize,
+    /// Free text annotation
+    pub annotation: String,
+}
+
+/// A [`StringInfo`] borrowing its fields from the line.
+#[derive(Debug, Clone, Copy)]
+pub struct StringInfoRef<'a> {
+    pub protein_id: &'a str,
+    pub preferred_name: &'a str,
+    pub protein_size: usize,
+    pub annotation: &'a str,
+}
+
+impl From<StringInfoRef<'_>> for StringInfo {
+    fn from(info: StringInfoRef) -> Self {
+        Self {
+            protein_id: info.protein_id.to_string(),
+            preferred_name: info.preferred_name.to_string(),
+            protein_size: info.protein_size,
+            annotation: info.annotation.to_string(),
+        }
+    }
+}
+
+impl Record for StringInfo {
+    fn parse(line: &str) -> Result<Self> {
+        Self::parse_ref(line.as_bytes()).map(Into::into)
+    }
+}
+
+impl RecordRef for StringInfo {
+    type Ref<'a> = StringInfoRef<'a>;
+
+    fn parse_ref(line: &[u8]) -> Result<StringInfoRef<'_>> {
+        let mut vals = line.split(|&byte| byte == b'\t');
+        Ok(StringInfoRef {
+            protein_id: str_column(&mut vals, "string_protein_id")?,
+            preferred_name: str_column(&mut vals, "preferred_name")?,
+            protein_size: str_column(&mut vals, "protein_size")?.trim().parse()?,
+            annotation: str_column(&mut vals, "annotation")?.trim_end(),
+        })
+    }
+}
+
+/// A line of `protein.aliases.v12.0.txt.gz`.
+#[derive(Debug, Clone)]
+pub struct StringAlias {
+    /// The STRING protein id, e.g. `23.BEL05_00025`
+    pub protein_id: String,
+    /// The alias, e.g. `A0A1E5IUA8`
+    pub alias: String,
+    /// Where the alias comes from, e.g. `UniProt_AC`
+    pub source: String,
+}
+
+/// A [`StringAlias`] borrowing its fields from the line.
+#[derive(Debug, Clone, Copy)]
+pub struct StringAliasRef<'a> {
+    pub protein_id: &'a str,
+    pub alias: &'a str,
+    pub source: &'a str,
+}
+
+impl From<StringAliasRef<'_>> for StringAlias {
+    fn from(alias: StringAliasRef) -> Self {
+        Self {
+            protein_id: alias.protein_id.to_string(),
+            alias: alias.alias.to_string(),
+            source: alias.source.to_string(),
+        }
+    }
+}
+
+impl Record for StringAlias {
+    fn parse(line: &str) -> Result<Self> {
+        Self::parse_ref(line.as_bytes()).map(Into::into)
+    }
+}
+
+impl RecordRef for StringAlias {
+    type Ref<'a> = StringAliasRef<'a>;
+
+    fn parse_ref(line: &[u8]) -> Result<StringAliasRef<'_>> {
+        let mut vals = line.split(|&byte| byte == b'\t');
+        Ok(StringAliasRef {
+            protein_id: str_column(&mut vals, "string_protein_id")?,
+            alias: str_column(&mut vals, "alias")?,
+            source: str_column(&mut vals, "source")?,
+        })
+    }
+}
+
+/// A line of `protein.enrichment.terms.v12.0.txt.gz`.
+#[derive(Debug, Clone)]
+pub struct StringEnrichmentTerm {
+    /// The STRING protein id, e.g. `100053.GCA_002009845_00001`
+    pub protein_id: String,
+    /// The category of the term, e.g. `Biological Process (Gene Ontology)`
+    pub category: String,
+    /// The term, e.g. `GO:0006139`
+    pub term: String,
+    /// The description of the term
+    pub description: String,
+}
+
+/// A [`StringEnrichmentTerm`] borrowing its fields from the line.
+#[derive(Debug, Clone, Copy)]
+pub struct StringEnrichmentTermRef<'a> {
+    pub protein_id: &'a str,
+    pub category: &'a str,
+    pub term: &'a str,
+    pub description: &'a str,
+}
+
+impl From<StringEnrichmentTermRef<'_>> for StringEnrichmentTerm {
+    fn from(term: StringEnrichmentTermRef) -> Self {
+        Self {
+            protein_id: term.protein_id.to_string(),
+            category: term.category.to_string(),
+            term: term.term.to_string(),
+            description: term.description.to_string(),
+        }
+    }
+}
+
+impl Record for StringEnrichmentTerm {
+    fn parse(line: &str) -> Result<Self> {
+        Self::parse_ref(line.as_bytes()).map(Into::into)
+    }
+}
+
+impl RecordRef for StringEnrichmentTerm {
+    type Ref<'a> = StringEnrichmentTermRef<'a>;
+
+    fn parse_ref(line: &[u8]) -> Result<StringEnrichmentTermRef<'_>> {
+        let mut vals = line.split(|&byte| byte == b'\t');
+        Ok(StringEnrichmentTermRef {
+            protein_id: str_column(&mut vals, "string_protein_id")?,
+            category: str_column(&mut vals, "category")?,
+            term: str_column(&mut vals, "term")?,
+            description: str_column(&mut vals, "description")?,
+        })
+    }
+}
+
+/// The names of the evidence channels of `protein.links.full.v12.0.txt.gz`,
+/// in the order they appear in the file.
+pub const STRING_LINK_CHANNELS: [&str; 13] = [
+    "neighborhood",
+    "neighborhood_transferred",
+    "fusion",
+    "cooccurence",
+    "homology",
+    "coexpression",
+    "coexpression_transferred",
+    "experiments",
+    "experiments_transferred",
+    "database",
+    "database_transferred",
+    "textmining",
+    "textmining_transferred",
+];
+
+/// The names of the values of [`StringLink::scores`], i.e. the channels
+/// followed by `combined_score`.
+pub const STRING_LINK_SCORES: [&str; STRING_LINK_CHANNELS.len() + 1] = {
+    let mut scores = ["combined_score"; STRING_LINK_CHANNELS.len() + 1];
+    let mut i = 0;
+    while i < STRING_LINK_CHANNELS.len() {
+        scores[i] = STRING_LINK_CHANNELS[i];
+        i += 1;
+    }
+    scores
+};
+
+/// A line of `protein.links.full.v12.0.txt.gz`.
+#[derive(Debug, Clone)]
+pub struct StringLink {
+    /// The STRING id of the first protein
+    pub protein1: String,
+    /// The STRING id of the second protein
+    pub protein2: String,
+    /// The scores of the channels in [`STRING_LINK_CHANNELS`]
+    pub channels: [u16; 13],
+    /// The combined score, in `0..1000`
+    pub combined_score: u16,
+}
+
+impl Record for StringLink {
+    const HEADER_LINES: usize = 1;
+
+    fn check_header(header: &str) -> Result<()> {
+        let vals = header.split(' ').collect::<Vec<_>>();
+        ensure!(vals.len() == 16, "Expected 16 columns, got {}", vals.len());
+        ensure!(
+            vals[2..15] == STRING_LINK_CHANNELS,
+            "Unexpected channels {:?}",
+            &vals[2..15]
+        );
+        ensure!(
+            vals[15] == "combined_score",
+            "Unexpected column {}",
+            vals[15]
+        );
+        Ok(())
+    }
+
+    fn parse(line: &str) -> Result<Self> {
+        Self::parse_ref(line.as_bytes()).map(Into::into)
+    }
+}
+
+impl RecordRef for StringLink {
+    type Ref<'a> = StringLinkRef<'a>;
+
+    fn parse_ref(line: &[u8]) -> Result<StringLinkRef<'_>> {
+        let mut vals = line.split(|&byte| byte == b' ');
+        let protein1 = str_column(&mut vals, "protein1")?;
+        let protein2 = str_column(&mut vals, "protein2")?;
+        let mut scores = [0; 14];
+        for (score, name) in scores.iter_mut().zip(STRING_LINK_SCORES) {
+            let val = vals
+                .next()
+                .with_context(|| format!("Missing column {}", name))?;
+            *score = parse_u16(val).with_context(|| format!("Invalid {}", name))?;
+        }
+        Ok(StringLinkRef {
+            protein1,
+            protein2,
+            scores,
+        })
+    }
+}
+
+impl StringLink {
+    /// The scores of the link, i.e. its channels and its combined score, in
+    /// the order of the columns of the file.
+    pub fn scores(&self) -> [u16; 14] {
+        let mut scores = [0; 14];
+        scores[..13].copy_from_slice(&self.channels);
+        scores[13] = self.combined_score;
+        scores
+    }
+}
+
+/// A [`StringLink`] borrowing the protein ids from the line.
+#[derive(Debug, Clone, Copy)]
+pub struct StringLinkRef<'a> {
+    pub protein1: &'a str,
+    pub protein2: &'a str,
+    /// The values of the columns in [`STRING_LINK_SCORES`]
+    pub scores: [u16; 14],
+}
+
+impl StringLinkRef<'_> {
+    /// The combined score, in `0..1000`.
+    pub fn combined_score(&self) -> u16 {
+        self.scores[13]
+    }
+}
+
+impl From<StringLinkRef<'_>> for StringLink {
+    fn from(link: StringLinkRef) -> Self {
+        let mut channels = [0; 13];
+        channels.copy_from_slice(&link.scores[..13]);
+        Self {
+            protein1: link.protein1.to_string(),
+            protein2: link.protein2.to_string(),
+            channels,
+            combined_score: link.combined_score(),
+        }
+    }
+}
@@ -0,0 +1,409 @@
+//! Reader for the UniProtKB flat files, e.g. `uniprot_sprot.dat.gz`.
+//!
+//! An entry spans many lines, each starting with a two-letter code, and ends
+//! with a `//` line. Only the lines we use are parsed: `ID`, `AC`, `DE`,
+//! `GN`, `OS`, `OX`, `DR` and `KW`; the others, e.g. the features and the
+//! sequence, are skipped. The evidence tags, e.g. `{ECO:0000256}`, are
+//! removed from the values.
+
+use super::open_input;
+use anyhow::{bail, ensure, Context, Result};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// A cross-reference of a `DR` line, e.g. `GO; GO:0046782; ...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniprotXref {
+    /// The database, e.g. `GO`, `InterPro` or `Pfam`
+    pub database: String,
+    /// The identifier in the database, e.g. `GO:0046782` or `IPR007031`
+    pub id: String,
+}
+
+/// An entry of a UniProtKB flat file.
+#[derive(Debug, Clone, Default)]
+pub struct UniprotEntry {
+    /// The entry name, e.g. `001R_FRG3G`
+    pub name: String,
+    /// Whether the entry is reviewed, i.e. from Swiss-Prot
+    pub reviewed: bool,
+    /// The length of the sequence in amino acids
+    pub length: usize,
+    /// The accessions, the primary one first, e.g. `Q6GZX4`
+    pub accessions: Vec<String>,
+    /// The recommended or submitted full name of the protein
+    pub description: Option<String>,
+    /// The gene names, e.g. `TP53`
+    pub gene_names: Vec<String>,
+    /// The organism, e.g. `Frog virus 3 (isolate Goorha) (FV-3)`
+    pub organism: String,
+    /// The NCBI taxon id of the organism, e.g. `654924`
+    pub taxon_id: Option<String>,
+    /// The cross-references of the `DR` lines, e.g. `InterPro` and
+    /// `IPR007031`, in the order of the file
+    pub xrefs: Vec<UniprotXref>,
+    /// The keywords, e.g. `Transcription`
+    pub keywords: Vec<String>,
+}
+
+impl UniprotEntry {
+    /// The primary accession.
+    pub fn accession(&self) -> &str {
+        &self.accessions[0]
+    }
+
+    /// The secondary accessions, i.e. those of the entries merged into this
+    /// one.
+    pub fn secondary_accessions(&self) -> &[String] {
+        &self.accessions[1..]
+    }
+}
+
+/// Remove the evidence tags and the spaces around a value.
+fn strip_evidence(value: &str) -> &str {
+    match value.find('{') {
+        Some(start) => value[..start].trim(),
+        None => value.trim(),
+    }
+}
+
+/// The values of a `;`-separated list, without evidence tags and the final
+/// `.` of the `KW` lines.
+fn list_values(text: &str) -> impl Iterator<Item = &str> {
+    text.split(';')
+        .map(|value| strip_evidence(value.trim().trim_end_matches('.')))
+        .filter(|value| !value.is_empty())
+}
+
+impl UniprotEntry {
+    /// Parse the value of an `ID` line, e.g.
+    /// `001R_FRG3G              Reviewed;         256 AA.`
+    fn parse_id(&mut self, value: &str) -> Result<()> {
+        let mut vals = value.split_whitespace();
+        self.name = vals.next().context("Missing entry name")?.to_string();
+        self.reviewed = match vals.next() {
+            Some("Reviewed;") => true,
+            Some("Unreviewed;") => false,
+            other => bail!("Unexpected status {:?}", other),
+        };
+        self.length = vals.next().context("Missing length")?.parse()?;
+        Ok(())
+    }
+
+    /// Parse the value of a `DE` line, keeping the first full name.
+    fn parse_de(&mut self, value: &str) {
+        if self.description.is_some() {
+            return;
+        }
+        let value = value.trim_start();
+        for prefix in ["RecName: Full=", "SubName: Full="] {
+            if let Some(name) = value.strip_prefix(prefix) {
+                self.description = Some(strip_evidence(name.trim_end_matches(';')).to_string());
+            }
+        }
+    }
+
+    /// Parse the value of an `OX` line, e.g. `NCBI_TaxID=654924;`.
+    fn parse_ox(&mut self, value: &str) -> Result<()> {
+        let taxon_id = value
+            .trim()
+            .strip_prefix("NCBI_TaxID=")
+            .with_context(|| format!("Unexpected taxon {:?}", value))?;
+        let taxon_id = strip_evidence(taxon_id.trim_end_matches(';'));
+        ensure!(
+            !taxon_id.is_empty() && taxon_id.bytes().all(|byte| byte.is_ascii_digit()),
+            "Invalid taxon id {:?}",
+            taxon_id
+        );
+        self.taxon_id = Some(taxon_id.to_string());
+        Ok(())
+    }
+
+    /// Parse the value of a `DR` line, e.g. `Pfam; PF04947; Pox_VLTF3; 1.`.
+    fn parse_dr(&mut self, value: &str) -> Result<()> {
+        let mut vals = strip_evidence(value).split(';').map(str::trim);
+        let database = vals.next().filter(|database| !database.is_empty());
+        let id = vals.next().map(|id| id.trim_end_matches('.'));
+        match (database, id) {
+            (Some(database), Some(id)) if !id.is_empty() => {
+                self.xrefs.push(UniprotXref {
+                    database: database.to_string(),
+                    id: id.to_string(),
+                });
+                Ok(())
+            }
+            _ => bail!("Unexpected cross-reference {:?}", value),
+        }
+    }
+
+    /// Parse the text of the `GN` lines of the entry, e.g.
+    /// `Name=TP53; Synonyms=P53;`, keeping the names.
+    fn parse_gn(&mut self, text: &str) {
+        for value in list_values(text) {
+            if let Some(name) = value.strip_prefix("Name=") {
+                self.gene_names.push(name.to_string());
+            }
+        }
+    }
+}
+
+/// Iterator over the entries of a UniProtKB flat file. After an entry that
+/// cannot be parsed, the next one is read from the line after its `//`.
+pub struct UniprotEntries {
+    path: PathBuf,
+    reader: Box<dyn BufRead>,
+    /// The current line, reused to read all of them
+    line: String,
+    line_number: usize,
+    /// Whether the `//` line of the last entry was read
+    terminated: bool,
+    /// Whether the file could not be read, so the iterator is over
+    failed: bool,
+}
+
+impl UniprotEntries {
+    /// Open the file at the given path.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        Ok(Self {
+            reader: open_input(&path)?,
+            path,
+            line: String::new(),
+            line_number: 0,
+            terminated: true,
+            failed: false,
+        })
+    }
+
+    /// The path of the file being read.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the lines of the next entry, returning `None` at the end of the
+    /// file.
+    fn read_entry(&mut self) -> Result<Option<UniprotEntry>> {
+        let mut entry = UniprotEntry::default();
+        let mut started = false;
+        // the GN, OS and KW values can span several lines
+        let mut gene_names = String::new();
+        let mut keywords = String::new();
+        self.terminated = false;
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                ensure!(!started, "Unterminated entry {}", entry.name);
+                return Ok(None);
+            }
+            self.line_number += 1;
+            let line = self.line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                continue;
+            }
+            started = true;
+            if line.starts_with("//") {
+                self.terminated = true;
+                break;
+            }
+            let code = line.get(..2).unwrap_or(line);
+            let value = line.get(5..).unwrap_or_default();
+            match code {
+                "ID" => entry.parse_id(value)?,
+                "AC" => entry
+                    .accessions
+                    .extend(list_values(value).map(str::to_string)),
+                "DE" => entry.parse_de(value),
+                // the genes of an entry are separated by `and` lines
+                "GN" if value.trim() == "and" => gene_names.push(';'),
+                "GN" => {
+                    gene_names.push_str(value);
+                    gene_names.push(' ');
+                }
+                "OS" => {
+                    if !entry.organism.is_empty() {
+                        entry.organism.push(' ');
+                    }
+                    entry.organism.push_str(value.trim());
+                }
+                "OX" => entry.parse_ox(value)?,
+                "DR" => entry.parse_dr(value)?,
+                "KW" => {
+                    keywords.push_str(value);
+                    keywords.push(' ');
+                }
+                _ => {}
+            }
+        }
+        ensure!(!entry.name.is_empty(), "Entry without an ID line");
+        ensure!(
+            !entry.accessions.is_empty(),
+            "Entry {} without an AC line",
+            entry.name
+        );
+        entry.parse_gn(&gene_names);
+        entry.keywords = list_values(&keywords).map(str::to_string).collect();
+        if entry.organism.ends_with('.') {
+            entry.organism.pop();
+        }
+        Ok(Some(entry))
+    }
+
+    /// Skip the rest of an entry after an error, up to its `//` line, so
+    /// that the next entry is read from its start.
+    fn skip_entry(&mut self) {
+        while !self.terminated {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => self.terminated = true,
+                Ok(_) => {
+                    self.line_number += 1;
+                    self.terminated = self.line.starts_with("//");
+                }
+                Err(_) => {
+                    self.failed = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for UniprotEntries {
+    type Item = Result<UniprotEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let entry = self.read_entry().with_context(|| {
+            format!(
+                "Could not parse {}:{}",
+                self.path.display(),
+                self.line_number
+            )
+        });
+        if entry.is_err() {
+            self.skip_entry();
+        }
+        entry.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir;
+    use std::fs;
+
+    /// Read the entries of a flat file with the given text.
+    fn read(text: &str) -> Result<Vec<UniprotEntry>> {
+        let dir = temp_dir(std::env::temp_dir());
+        let path = Path::new(&dir).join("uniprot.dat");
+        fs::write(&path, text)?;
+        let entries = UniprotEntries::open(&path)?.collect::<Result<Vec<_>>>();
+        fs::remove_dir_all(&dir)?;
+        entries
+    }
+
+    #[test]
+    fn test_gene_names() -> Result<()> {
+        let entries = read(
+            "ID   HBA_HUMAN               Reviewed;         142 AA.
+AC   P69905; P01922;
+GN   Name=HBA1 {ECO:0000312|HGNC:HGNC:4823};
+GN   and
+GN   Name=HBA2; Synonyms=HBA
+GN   {ECO:0000312|HGNC:HGNC:4824};
+OS   Homo sapiens
+OS   (Human).
+OX   NCBI_TaxID=9606;
+KW   3D-structure; Acetylation;
+KW   Heme.
+//
+",
+        )?;
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.accession(), "P69905");
+        assert_eq!(entry.secondary_accessions(), ["P01922"]);
+        assert_eq!(entry.gene_names, ["HBA1", "HBA2"]);
+        assert_eq!(entry.organism, "Homo sapiens (Human)");
+        assert_eq!(entry.keywords, ["3D-structure", "Acetylation", "Heme"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_evidence() -> Result<()> {
+        let entries = read(
+            "ID   A0A0B4J2F0_HUMAN        Unreviewed;        54 AA.
+AC   A0A0B4J2F0;
+DE   SubName: Full=Protein PIGBOS1 {ECO:0000313|Ensembl:ENSP00000484893.1};
+OX   NCBI_TaxID=9606 {ECO:0000313|Ensembl:ENSP00000484893.1, ECO:0000313|Proteomes:UP000005640};
+DR   Pfam; PF04947; Pox_VLTF3; 1. {ECO:0000256|ARBA:ARBA00000001}
+DR   GO; GO:0046782; P:regulation of viral transcription; IEA:InterPro.
+//
+",
+        )?;
+        let entry = &entries[0];
+        assert!(!entry.reviewed);
+        assert_eq!(entry.length, 54);
+        assert_eq!(entry.description.as_deref(), Some("Protein PIGBOS1"));
+        assert_eq!(entry.taxon_id.as_deref(), Some("9606"));
+        assert_eq!(
+            entry.xrefs,
+            [
+                UniprotXref {
+                    database: "Pfam".to_string(),
+                    id: "PF04947".to_string(),
+                },
+                UniprotXref {
+                    database: "GO".to_string(),
+                    id: "GO:0046782".to_string(),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unterminated_entry() {
+        let err = read(
+            "ID   001R_FRG3G              Reviewed;         256 AA.
+AC   Q6GZX4;
+//
+ID   002L_FRG3G              Reviewed;         320 AA.
+AC   Q6GZX3;
+",
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("Unterminated entry 002L_FRG3G"));
+    }
+
+    #[test]
+    fn test_error_recovery() -> Result<()> {
+        let dir = temp_dir(std::env::temp_dir());
+        let path = Path::new(&dir).join("uniprot.dat");
+        fs::write(
+            &path,
+            "ID   001R_FRG3G              Reviewed;         256 AA.
+AC   Q6GZX4;
+OX   NCBI_TaxID=FV3;
+DR   Pfam; PF04947; Pox_VLTF3; 1.
+//
+ID   002L_FRG3G              Reviewed;         320 AA.
+AC   Q6GZX3;
+OX   NCBI_TaxID=654924;
+//
+",
+        )?;
+        let mut entries = UniprotEntries::open(&path)?;
+        let err = entries.next().unwrap().unwrap_err();
+        assert!(format!("{:#}", err).contains("uniprot.dat:3"));
+        let entry = entries.next().unwrap()?;
+        assert_eq!(entry.accession(), "Q6GZX3");
+        assert_eq!(entry.taxon_id.as_deref(), Some("654924"));
+        assert!(entries.next().is_none());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
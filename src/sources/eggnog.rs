@@ -0,0 +1,63 @@
+//! The eggNOG 6 orthologous groups.
+
+use super::{NodeIds, PushArc, Source, SourceReport};
+use crate::names;
+use crate::node_type::NodeType;
+use crate::readers::{EggnogGroup, Records};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// `e6.og2seqs_and_species.tsv`: each group is linked from the NCBI taxon of
+/// its last common ancestor, and is a star with arcs in both directions
+/// between the group and its STRING proteins.
+pub struct Groups {
+    path: PathBuf,
+}
+
+impl Groups {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Source for Groups {
+    fn name(&self) -> &str {
+        "eggnog"
+    }
+
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        Records::<EggnogGroup>::open(&self.path)?.try_for_each_ref(|group| {
+            add(NodeType::Taxon, names::ncbi_taxon(group.taxon_id))?;
+            add(NodeType::EggnogGroup, names::eggnog_group(group.group))
+        })?;
+        Ok(SourceReport::default())
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        Records::<EggnogGroup>::open(&self.path)?.try_for_each_ref(|group| {
+            let ncbi_species_id = ids.get(NodeType::Taxon, &names::ncbi_taxon(group.taxon_id))?;
+            let eggnog_group_id =
+                ids.get(NodeType::EggnogGroup, &names::eggnog_group(group.group))?;
+            push(
+                ncbi_species_id,
+                eggnog_group_id,
+                "has_orthologous_group",
+                &[],
+            )?;
+
+            // We have switched from a clique representation
+            // to a star representation so we don't need to add
+            // all the edges.
+            for protein in group.proteins() {
+                let src_id = ids.get(NodeType::Protein, &names::node_ref(protein))?;
+                push(eggnog_group_id, src_id, "has_member", &[])?;
+                push(src_id, eggnog_group_id, "member_of", &[])?;
+            }
+            Ok(())
+        })?;
+        Ok(SourceReport::default())
+    }
+}
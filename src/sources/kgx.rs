@@ -0,0 +1,210 @@
+//! The ontologies in KGX TSV format from kg-hub.
+
+use super::{NodeIds, PushArc, PushAttr, Source, SourceReport};
+use crate::attrs::{AttrColumn, AttrValue};
+use crate::names;
+use crate::node_type::NodeType;
+use crate::readers::{
+    with_kgx_archive_member, KgxEdge, KgxNode, KgxRecord, KgxRecords, KGX_MULTI_VALUE_SEPARATOR,
+};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// The end of the name of the nodes file of an ontology.
+const NODES_SUFFIX: &str = "_nodes.tsv";
+/// The end of the name of the edges file of an ontology.
+const EDGES_SUFFIX: &str = "_edges.tsv";
+
+/// Where the files of an ontology are.
+enum Files {
+    /// The extracted `*_kgx_tsv_nodes.tsv` and `*_kgx_tsv_edges.tsv`
+    Tsv { nodes: PathBuf, edges: PathBuf },
+    /// A `*_kgx_tsv.tar.gz` archive containing both
+    Archive(PathBuf),
+}
+
+/// Joins the predicate and the relation of an edge in the labels of its arc;
+/// it cannot occur in a CURIE, nor in a single KGX value.
+pub const RELATION_SEPARATOR: char = KGX_MULTI_VALUE_SEPARATOR;
+
+/// How many edges of a KGX file have each predicate and relation.
+#[derive(Debug, Clone)]
+pub struct PredicateFrequencies {
+    /// The edges file
+    pub path: PathBuf,
+    /// The predicates, the relations if the file has them, and their counts,
+    /// the most frequent first
+    pub frequencies: Vec<(String, Option<String>, usize)>,
+}
+
+/// An ontology as a pair of `*_kgx_tsv_nodes.tsv` and `*_kgx_tsv_edges.tsv`
+/// files, possibly inside a `*_kgx_tsv.tar.gz` archive. Each KGX edge
+/// becomes an arc from the subject to the object, whose
+/// predicate is the one of the edge, e.g. `biolink:subclass_of`, followed by
+/// [`RELATION_SEPARATOR`] and its relation, e.g.
+/// `biolink:related_to|RO:0002211`, unless disabled with
+/// [`Ontology::with_relations`].
+///
+/// The edge pass reports the frequency of each predicate and relation of the
+/// file, see [`PredicateFrequencies`]. The categories, joined by `|`, the
+/// name and the description of each node are its attributes.
+pub struct Ontology {
+    name: String,
+    files: Files,
+    relations: bool,
+}
+
+impl Ontology {
+    pub fn new<P: Into<PathBuf>, Q: Into<PathBuf>>(
+        name: &str,
+        nodes_path: P,
+        edges_path: Q,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            files: Files::Tsv {
+                nodes: nodes_path.into(),
+                edges: edges_path.into(),
+            },
+            relations: true,
+        }
+    }
+
+    /// An ontology read from a KGX archive, e.g. `go_kgx_tsv.tar.gz`.
+    pub fn from_archive<P: Into<PathBuf>>(name: &str, archive_path: P) -> Self {
+        Self {
+            name: name.to_string(),
+            files: Files::Archive(archive_path.into()),
+            relations: true,
+        }
+    }
+
+    /// Call `f` on the records of the nodes or of the edges file, according
+    /// to `suffix`.
+    fn read<T: KgxRecord, R>(
+        &self,
+        suffix: &str,
+        f: impl FnOnce(KgxRecords<'_, T>) -> Result<R>,
+    ) -> Result<R> {
+        match &self.files {
+            Files::Tsv { nodes, edges } => {
+                let path = if suffix == NODES_SUFFIX { nodes } else { edges };
+                f(KgxRecords::open(path)?)
+            }
+            Files::Archive(path) => with_kgx_archive_member(path, suffix, f),
+        }
+    }
+
+    /// Whether to add the relations of the edges to their predicates.
+    pub fn with_relations(mut self, relations: bool) -> Self {
+        self.relations = relations;
+        self
+    }
+}
+
+impl Source for Ontology {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        let malformed = self.read(NODES_SUFFIX, |mut nodes: KgxRecords<KgxNode>| {
+            for node in &mut nodes {
+                let node_name = names::node(&node?.id);
+                add(NodeType::of_term(&node_name), node_name)?;
+            }
+            Ok(nodes.malformed().cloned())
+        })?;
+        Ok(SourceReport {
+            malformed: malformed.into_iter().collect(),
+            ..Default::default()
+        })
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        let mut frequencies = HashMap::<(String, Option<String>), usize>::new();
+        let (edges_path, malformed) =
+            self.read(EDGES_SUFFIX, |mut edges: KgxRecords<KgxEdge>| {
+                for edge in &mut edges {
+                    let edge = edge?;
+                    let subject = names::node(&edge.subject);
+                    let object = names::node(&edge.object);
+                    let subject_id = ids.get(NodeType::of_term(&subject), &subject)?;
+                    let object_id = ids.get(NodeType::of_term(&object), &object)?;
+                    match (&edge.relation, self.relations) {
+                        (Some(relation), true) => push(
+                            subject_id,
+                            object_id,
+                            &format!("{}{}{}", edge.predicate, RELATION_SEPARATOR, relation),
+                            &[],
+                        )?,
+                        _ => push(subject_id, object_id, &edge.predicate, &[])?,
+                    }
+                    *frequencies
+                        .entry((edge.predicate, edge.relation))
+                        .or_default() += 1;
+                }
+                Ok((edges.path().to_owned(), edges.malformed().cloned()))
+            })?;
+
+        let mut frequencies = frequencies
+            .into_iter()
+            .map(|((predicate, relation), count)| (predicate, relation, count))
+            .collect::<Vec<_>>();
+        frequencies.sort_by(|(a, a_relation, a_count), (b, b_relation, b_count)| {
+            b_count
+                .cmp(a_count)
+                .then((a, a_relation).cmp(&(b, b_relation)))
+        });
+        Ok(SourceReport {
+            predicates: vec![PredicateFrequencies {
+                path: edges_path,
+                frequencies,
+            }],
+            malformed: malformed.into_iter().collect(),
+            ..Default::default()
+        })
+    }
+
+    fn attr_columns(&self) -> Vec<AttrColumn> {
+        vec![
+            AttrColumn::text("category"),
+            AttrColumn::text("name"),
+            AttrColumn::text("description"),
+        ]
+    }
+
+    fn node_attrs(&self, ids: &mut dyn NodeIds, push: &mut PushAttr) -> Result<SourceReport> {
+        let malformed = self.read(NODES_SUFFIX, |mut nodes: KgxRecords<KgxNode>| {
+            for node in &mut nodes {
+                let node = node?;
+                let node_name = names::node(&node.id);
+                let node_id = ids.get(NodeType::of_term(&node_name), &node_name)?;
+                if !node.category.is_empty() {
+                    push(
+                        node_id,
+                        "category",
+                        AttrValue::Text(
+                            &node.category.join(&KGX_MULTI_VALUE_SEPARATOR.to_string()),
+                        ),
+                    )?;
+                }
+                if let Some(name) = &node.name {
+                    push(node_id, "name", AttrValue::Text(name))?;
+                }
+                if let Some(description) = &node.description {
+                    push(node_id, "description", AttrValue::Text(description))?;
+                }
+            }
+            Ok(nodes.malformed().cloned())
+        })?;
+        Ok(SourceReport {
+            malformed: malformed.into_iter().collect(),
+            ..Default::default()
+        })
+    }
+}
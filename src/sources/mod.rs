@@ -0,0 +1,185 @@
+//! The datasets that contribute nodes and arcs to the graph.
+//!
+//! Each dataset implements [`Source`], which yields both the node names for
+//! the vocabulary pass and the arcs for the edge pass, so the naming
+//! conventions of a dataset live in a single place. The endpoints of the
+//! arcs are resolved through [`NodeIds`], so what happens to the names that
+//! are not in the vocabulary is decided by the caller, see
+//! [`crate::missing`]. A source may also give typed attributes of its
+//! nodes, see [`crate::attrs`]. The [`Registry`] holds
+//! the sources to use for a build, in the order they are scanned, and is
+//! usually built from a [`crate::config::Config`].
+
+use crate::attrs::{AttrColumn, AttrValue};
+use crate::node_type::NodeType;
+use crate::readers::MalformedLines;
+use anyhow::Result;
+
+pub mod eggnog;
+pub mod kgx;
+pub mod oma;
+pub mod string;
+pub mod uniprot;
+
+/// A dataset that contributes nodes and arcs to the graph.
+pub trait Source {
+    /// A short unique name of the source, e.g. `oma_groups`.
+    fn name(&self) -> &str;
+
+    /// Call `add` on the type and name of every node of the source. The same
+    /// name can be passed more than once. Returns what the source counted on
+    /// the way, like [`Source::arcs`].
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport>;
+
+    /// Call `push` on every arc of the source, with the ids of its endpoints
+    /// given by `ids` and its predicate, e.g. `interacts_with`, which with
+    /// the name of the source is the [`crate::labels::EdgeType`] of the arc.
+    /// An endpoint is `None` if its name is missing from the vocabulary and
+    /// the arc must be dropped. The last argument holds one value for each
+    /// of [`Source::weight_names`]. Returns what the source counted on the
+    /// way, for the caller to print.
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport>;
+
+    /// The names of the weights of the arcs of the source, stored by
+    /// [`crate::weights`]. Most sources have none.
+    fn weight_names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The columns of the attributes of the nodes of the source, stored by
+    /// [`crate::attrs`]. Most sources have none.
+    fn attr_columns(&self) -> Vec<AttrColumn> {
+        Vec::new()
+    }
+
+    /// Call `push` on every attribute of the nodes of the source, with the
+    /// id of the node given by `ids`, the name of one of
+    /// [`Source::attr_columns`] and the value. The node is `None` if its name
+    /// is missing from the vocabulary and the attribute must be dropped.
+    /// Returns what the source counted on the way, like [`Source::arcs`].
+    fn node_attrs(&self, _ids: &mut dyn NodeIds, _push: &mut PushAttr) -> Result<SourceReport> {
+        Ok(SourceReport::default())
+    }
+
+    /// Like [`Source::arcs`], but each of `pushes` may be called from its own
+    /// thread, so that the sources with huge files can parse them in
+    /// parallel, see [`crate::readers::par_for_each`]. The arcs are the same,
+    /// in any order. By default they are all pushed through the first
+    /// callback by [`Source::arcs`].
+    fn par_arcs(
+        &self,
+        ids: &dyn SharedNodeIds,
+        pushes: &mut [Box<SendPushArc>],
+    ) -> Result<SourceReport> {
+        self.arcs(&mut { ids }, &mut *pushes[0])
+    }
+}
+
+/// What a source counted while reading its files, returned to the caller to
+/// print. Most sources have nothing to report.
+#[derive(Debug, Clone, Default)]
+pub struct SourceReport {
+    /// The frequencies of the predicates of the edges of each KGX file
+    pub predicates: Vec<kgx::PredicateFrequencies>,
+    /// The STRING links kept and discarded
+    pub links: Option<string::LinksCounts>,
+    /// The lines of each KGX file with a malformed quoted field
+    pub malformed: Vec<MalformedLines>,
+}
+
+/// The callback of [`Source::arcs`], taking the endpoints, the predicate and
+/// the weights of an arc.
+pub type PushArc<'a> = dyn FnMut(Option<usize>, Option<usize>, &str, &[u16]) -> Result<()> + 'a;
+
+/// A [`PushArc`] that can be moved to a worker thread, see
+/// [`Source::par_arcs`].
+pub type SendPushArc<'a> =
+    dyn FnMut(Option<usize>, Option<usize>, &str, &[u16]) -> Result<()> + Send + 'a;
+
+/// The callback of [`Source::node_attrs`], taking the node, the name of the
+/// column and the value of an attribute.
+pub type PushAttr<'a> = dyn FnMut(Option<usize>, &str, AttrValue) -> Result<()> + 'a;
+
+/// Maps the names of the endpoints of the arcs to node ids.
+pub trait NodeIds {
+    /// The id of a node given its type and name, or `None` if the arcs that
+    /// use it must be dropped.
+    fn get(&mut self, node_type: NodeType, node_name: &str) -> Result<Option<usize>>;
+
+    /// Like [`NodeIds::get`] for a name that cannot be in the vocabulary,
+    /// e.g. one derived from a malformed identifier, so that it follows the
+    /// missing policy of the source.
+    fn malformed(&mut self, node_type: NodeType, node_name: &str) -> Result<Option<usize>>;
+}
+
+/// A [`NodeIds`] that can be shared by the worker threads of
+/// [`Source::par_arcs`].
+pub trait SharedNodeIds: Sync {
+    /// The id of a node given its type and name, or `None` if the arcs that
+    /// use it must be dropped.
+    fn get(&self, node_type: NodeType, node_name: &str) -> Result<Option<usize>>;
+
+    /// See [`NodeIds::malformed`].
+    fn malformed(&self, node_type: NodeType, node_name: &str) -> Result<Option<usize>>;
+}
+
+impl NodeIds for &dyn SharedNodeIds {
+    fn get(&mut self, node_type: NodeType, node_name: &str) -> Result<Option<usize>> {
+        SharedNodeIds::get(*self, node_type, node_name)
+    }
+
+    fn malformed(&mut self, node_type: NodeType, node_name: &str) -> Result<Option<usize>> {
+        SharedNodeIds::malformed(*self, node_type, node_name)
+    }
+}
+
+/// The KGX ontologies that we load by default, by file prefix.
+pub const KGX_ONTOLOGIES: &[&str] = &[
+    "ncbitaxon",
+    "go",
+    "bto",
+    "cl",
+    "doid",
+    "fbcv",
+    "hp",
+    "mp",
+    "zp",
+];
+
+/// An ordered list of sources.
+#[derive(Default)]
+pub struct Registry {
+    sources: Vec<Box<dyn Source>>,
+}
+
+impl Registry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source at the end of the registry.
+    pub fn register<S: Source + 'static>(&mut self, source: S) -> &mut Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+
+    /// Keep only the sources for which `f` returns true.
+    pub fn retain(&mut self, mut f: impl FnMut(&dyn Source) -> bool) -> &mut Self {
+        self.sources.retain(|source| f(source.as_ref()));
+        self
+    }
+
+    /// Get a source by name.
+    pub fn get(&self, name: &str) -> Option<&dyn Source> {
+        self.iter().find(|source| source.name() == name)
+    }
+
+    /// Iterate on the sources in the order they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Source> {
+        self.sources.iter().map(|source| source.as_ref())
+    }
+}
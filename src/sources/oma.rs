@@ -0,0 +1,164 @@
+//! The OMA browser orthologous groups, species and UniProt mapping.
+
+use super::{NodeIds, PushArc, PushAttr, Source, SourceReport};
+use crate::attrs::{AttrColumn, AttrValue};
+use crate::names;
+use crate::node_type::NodeType;
+use crate::readers::{OmaGroup, OmaSpecies, OmaUniprot, Records};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// `oma-groups.txt.gz`: each group is a star with arcs between the group
+/// node and its entries, and each entry is linked from its OMA species code.
+/// The entries too short to have a species code follow the missing policy of
+/// the source.
+pub struct Groups {
+    path: PathBuf,
+}
+
+impl Groups {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Source for Groups {
+    fn name(&self) -> &str {
+        "oma_groups"
+    }
+
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        Records::<OmaGroup>::open(&self.path)?.try_for_each_ref(|group| {
+            add(NodeType::OmaGroup, names::oma_group(group.group))?;
+            for entry in group.entries() {
+                add(NodeType::OmaEntry, names::node(entry))?;
+            }
+            Ok(())
+        })?;
+        Ok(SourceReport::default())
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        Records::<OmaGroup>::open(&self.path)?.try_for_each_ref(|group| {
+            let oma_group_id = ids.get(NodeType::OmaGroup, &names::oma_group(group.group))?;
+
+            // We have switched from a clique representation
+            // to a star representation so we don't need to add
+            // all the edges.
+            for entry in group.entries() {
+                let src_id = ids.get(NodeType::OmaEntry, &names::node_ref(entry))?;
+                let src_prefix = match names::oma_species_of_entry(entry) {
+                    Some(species) => ids.get(NodeType::OmaSpecies, species)?,
+                    None => ids.malformed(NodeType::OmaSpecies, entry)?,
+                };
+                push(src_prefix, src_id, "has_entry", &[])?;
+                push(src_id, oma_group_id, "member_of", &[])?;
+                push(oma_group_id, src_id, "has_member", &[])?;
+            }
+            Ok(())
+        })?;
+        Ok(SourceReport::default())
+    }
+}
+
+/// `oma-species.txt`: arcs in both directions between each OMA species code
+/// and its NCBI taxon. The scientific name of each species is an attribute
+/// of its code.
+pub struct Species {
+    path: PathBuf,
+}
+
+impl Species {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Source for Species {
+    fn name(&self) -> &str {
+        "oma_species"
+    }
+
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        for species in Records::<OmaSpecies>::open(&self.path)? {
+            let species = species?;
+            add(NodeType::OmaSpecies, names::node(&species.code))?;
+            add(NodeType::Taxon, names::ncbi_taxon(&species.ncbi_taxon_id))?;
+        }
+        Ok(SourceReport::default())
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        for species in Records::<OmaSpecies>::open(&self.path)? {
+            let species = species?;
+            let oma_code = ids.get(NodeType::OmaSpecies, &names::node(&species.code))?;
+            let ncbi_code = ids.get(NodeType::Taxon, &names::ncbi_taxon(&species.ncbi_taxon_id))?;
+            push(oma_code, ncbi_code, "same_as", &[])?;
+            push(ncbi_code, oma_code, "same_as", &[])?;
+        }
+        Ok(SourceReport::default())
+    }
+
+    fn attr_columns(&self) -> Vec<AttrColumn> {
+        vec![AttrColumn::text("scientific_name")]
+    }
+
+    fn node_attrs(&self, ids: &mut dyn NodeIds, push: &mut PushAttr) -> Result<SourceReport> {
+        for species in Records::<OmaSpecies>::open(&self.path)? {
+            let species = species?;
+            let oma_code = ids.get(NodeType::OmaSpecies, &names::node(&species.code))?;
+            push(
+                oma_code,
+                "scientific_name",
+                AttrValue::Text(&species.scientific_name),
+            )?;
+        }
+        Ok(SourceReport::default())
+    }
+}
+
+/// `oma-uniprot.txt.gz`: arcs in both directions between each OMA entry and
+/// its UniProt ids.
+pub struct Uniprot {
+    path: PathBuf,
+}
+
+impl Uniprot {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Source for Uniprot {
+    fn name(&self) -> &str {
+        "oma_uniprot"
+    }
+
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        Records::<OmaUniprot>::open(&self.path)?.try_for_each_ref(|mapping| {
+            add(NodeType::OmaEntry, names::node(mapping.oma_id))?;
+            add(NodeType::Uniprot, names::node(mapping.uniprot_id))
+        })?;
+        Ok(SourceReport::default())
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        Records::<OmaUniprot>::open(&self.path)?.try_for_each_ref(|mapping| {
+            let oma_code_id = ids.get(NodeType::OmaEntry, &names::node_ref(mapping.oma_id))?;
+            let uniprot_code_id =
+                ids.get(NodeType::Uniprot, &names::node_ref(mapping.uniprot_id))?;
+            push(oma_code_id, uniprot_code_id, "same_as", &[])?;
+            push(uniprot_code_id, oma_code_id, "same_as", &[])
+        })?;
+        Ok(SourceReport::default())
+    }
+}
@@ -0,0 +1,603 @@
+//! The STRING protein info, aliases, enrichment terms and links.
+
+use super::{NodeIds, PushArc, PushAttr, SendPushArc, SharedNodeIds, Source, SourceReport};
+use crate::attrs::{AttrColumn, AttrValue};
+use crate::filter::Filter;
+use crate::names;
+use crate::node_type::NodeType;
+use crate::readers::{
+    par_for_each, Records, StringAlias, StringAliasRef, StringEnrichmentTerm,
+    StringEnrichmentTermRef, StringInfo, StringInfoRef, StringLink, StringLinkRef,
+    STRING_LINK_SCORES,
+};
+use anyhow::{ensure, Context, Result};
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+/// `protein.info.v12.0.txt.gz`: all the STRING proteins, with arcs in both
+/// directions to the NCBI taxon in the prefix of their id, see
+/// [`names::string_protein_taxon`]. The taxa add no node names, as they come
+/// from the NCBITaxon ontology, so the taxa missing from it, and the ids
+/// without a taxon, follow the missing policy of the source. The preferred name, size and annotation of
+/// each protein are its attributes.
+pub struct Info {
+    path: PathBuf,
+}
+
+impl Info {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Push the arcs between a protein and its taxon.
+    fn taxon_arcs(info: StringInfoRef, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<()> {
+        let protein_id = ids.get(NodeType::Protein, &names::node_ref(info.protein_id))?;
+        let taxon_id = match names::string_protein_taxon(info.protein_id) {
+            Some(taxon_id) => ids.get(NodeType::Taxon, &names::ncbi_taxon(taxon_id))?,
+            None => ids.malformed(NodeType::Taxon, info.protein_id)?,
+        };
+        push(protein_id, taxon_id, "in_taxon", &[])?;
+        push(taxon_id, protein_id, "taxon_of", &[])
+    }
+}
+
+impl Source for Info {
+    fn name(&self) -> &str {
+        "string_info"
+    }
+
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        Records::<StringInfo>::open(&self.path)?
+            .try_for_each_ref(|info| add(NodeType::Protein, names::node(info.protein_id)))?;
+        Ok(SourceReport::default())
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        Records::<StringInfo>::open(&self.path)?
+            .try_for_each_ref(|info| Self::taxon_arcs(info, ids, push))?;
+        Ok(SourceReport::default())
+    }
+
+    fn attr_columns(&self) -> Vec<AttrColumn> {
+        vec![
+            AttrColumn::text("preferred_name"),
+            AttrColumn::int("protein_size"),
+            AttrColumn::text("annotation"),
+        ]
+    }
+
+    fn node_attrs(&self, ids: &mut dyn NodeIds, push: &mut PushAttr) -> Result<SourceReport> {
+        Records::<StringInfo>::open(&self.path)?.try_for_each_ref(|info| {
+            let protein_id = ids.get(NodeType::Protein, &names::node_ref(info.protein_id))?;
+            push(
+                protein_id,
+                "preferred_name",
+                AttrValue::Text(info.preferred_name),
+            )?;
+            push(
+                protein_id,
+                "protein_size",
+                AttrValue::Int(info.protein_size as u64),
+            )?;
+            push(protein_id, "annotation", AttrValue::Text(info.annotation))
+        })?;
+        Ok(SourceReport::default())
+    }
+
+    fn par_arcs(
+        &self,
+        ids: &dyn SharedNodeIds,
+        pushes: &mut [Box<SendPushArc>],
+    ) -> Result<SourceReport> {
+        par_for_each::<StringInfo, _, _>(&self.path, pushes, |push, info| {
+            Self::taxon_arcs(info, &mut { ids }, &mut **push)
+        })?;
+        Ok(SourceReport::default())
+    }
+}
+
+/// `protein.aliases.v12.0.txt.gz`: arcs in both directions between each
+/// STRING protein and its aliases from the sources in `alias_sources`, e.g.
+/// `UniProt_AC`, `RefSeq` or `Ensembl_gene`. The aliases of each source are
+/// nodes in its namespace, see [`names::string_alias`], and their arcs have
+/// the source as predicate, e.g. `ensembl_gene`.
+pub struct Aliases {
+    path: PathBuf,
+    sources: Vec<AliasSource>,
+}
+
+/// A source of aliases in `protein.aliases`.
+struct AliasSource {
+    /// The name of the source in the file, e.g. `Ensembl_gene`
+    name: String,
+    /// The prefix of its aliases, e.g. `ENSEMBL`, or empty to keep them as
+    /// they are
+    namespace: String,
+    node_type: NodeType,
+    /// The predicate of its arcs, i.e. its lowercased name
+    predicate: String,
+}
+
+impl Aliases {
+    /// The aliases of the sources in the keys of `alias_sources`, each with
+    /// its namespace. The UniProt ACs, e.g. of `UniProt_AC` and
+    /// `BLAST_UniProt_AC`, are UniProt nodes, and the others are aliases.
+    pub fn new<P: Into<PathBuf>>(path: P, alias_sources: &BTreeMap<String, String>) -> Self {
+        let sources = alias_sources
+            .iter()
+            .map(|(name, namespace)| AliasSource {
+                name: name.clone(),
+                namespace: namespace.clone(),
+                node_type: if name.to_ascii_uppercase().ends_with("UNIPROT_AC") {
+                    NodeType::Uniprot
+                } else {
+                    NodeType::Alias
+                },
+                predicate: name.to_ascii_lowercase(),
+            })
+            .collect();
+        Self {
+            path: path.into(),
+            sources,
+        }
+    }
+
+    /// The source of an alias, if it is one of ours.
+    fn source(&self, alias: &StringAliasRef) -> Option<&AliasSource> {
+        self.sources
+            .iter()
+            .find(|source| source.name.eq_ignore_ascii_case(alias.source))
+    }
+
+    /// Push the arcs of an alias, if it comes from one of our sources.
+    fn alias_arcs(
+        &self,
+        alias: StringAliasRef,
+        ids: &mut dyn NodeIds,
+        push: &mut PushArc,
+    ) -> Result<()> {
+        let Some(source) = self.source(&alias) else {
+            return Ok(());
+        };
+        let protein_id = ids.get(NodeType::Protein, &names::node_ref(alias.protein_id))?;
+        let alias_name = names::string_alias(&source.namespace, alias.alias);
+        let alias_id = ids.get(source.node_type, &alias_name)?;
+        push(protein_id, alias_id, &source.predicate, &[])?;
+        push(alias_id, protein_id, &source.predicate, &[])
+    }
+}
+
+impl Source for Aliases {
+    fn name(&self) -> &str {
+        "string_aliases"
+    }
+
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        Records::<StringAlias>::open(&self.path)?.try_for_each_ref(|alias| {
+            let Some(source) = self.source(&alias) else {
+                return Ok(());
+            };
+            let alias_name = names::string_alias(&source.namespace, alias.alias);
+            add(source.node_type, alias_name.into_owned())
+        })?;
+        Ok(SourceReport::default())
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        Records::<StringAlias>::open(&self.path)?
+            .try_for_each_ref(|alias| self.alias_arcs(alias, ids, push))?;
+        Ok(SourceReport::default())
+    }
+
+    fn par_arcs(
+        &self,
+        ids: &dyn SharedNodeIds,
+        pushes: &mut [Box<SendPushArc>],
+    ) -> Result<SourceReport> {
+        par_for_each::<StringAlias, _, _>(&self.path, pushes, |push, alias| {
+            self.alias_arcs(alias, &mut { ids }, &mut **push)
+        })?;
+        Ok(SourceReport::default())
+    }
+}
+
+/// `protein.enrichment.terms.v12.0.txt.gz`: arcs in both directions between
+/// each STRING protein and its terms in the ontologies of
+/// [`names::ENRICHMENT_FILTER`], whose predicates end with the category of
+/// the term, see [`names::enrichment_category`], e.g.
+/// `annotated_with_biological_process`. The description of each term is its
+/// `string_description` attribute, the one of its first line, which in
+/// STRING is the same on all the lines of a term.
+pub struct EnrichmentTerms {
+    path: PathBuf,
+}
+
+/// The predicates of the arcs of the categories met so far, so they are
+/// built once per category rather than once per line.
+#[derive(Default)]
+struct CategoryPredicates {
+    /// Each category, with the predicates of the arcs from its proteins to
+    /// its terms and back
+    predicates: Vec<(String, String, String)>,
+}
+
+impl CategoryPredicates {
+    /// The predicates of the arcs from the proteins to the terms of a
+    /// category, and back.
+    fn get(&mut self, category: &str) -> (&str, &str) {
+        let index = match self.predicates.iter().position(|(c, _, _)| c == category) {
+            Some(index) => index,
+            None => {
+                let label = names::enrichment_category(category);
+                self.predicates.push((
+                    category.to_string(),
+                    format!("annotated_with_{}", label),
+                    format!("annotates_{}", label),
+                ));
+                self.predicates.len() - 1
+            }
+        };
+        let (_, annotated_with, annotates) = &self.predicates[index];
+        (annotated_with, annotates)
+    }
+}
+
+impl EnrichmentTerms {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Push the arcs of a term, if it is in one of our ontologies.
+    fn term_arcs(
+        term: StringEnrichmentTermRef,
+        predicates: &mut CategoryPredicates,
+        ids: &mut dyn NodeIds,
+        push: &mut PushArc,
+    ) -> Result<()> {
+        let Some(term_name) = names::enrichment_term_ref(term.term) else {
+            return Ok(());
+        };
+        let string_protein_id = ids.get(NodeType::Protein, &names::node_ref(term.protein_id))?;
+        let term_id = ids.get(NodeType::OntologyTerm, &term_name)?;
+        let (annotated_with, annotates) = predicates.get(term.category);
+        push(string_protein_id, term_id, annotated_with, &[])?;
+        push(term_id, string_protein_id, annotates, &[])
+    }
+}
+
+impl Source for EnrichmentTerms {
+    fn name(&self) -> &str {
+        "string_enrichment_terms"
+    }
+
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        Records::<StringEnrichmentTerm>::open(&self.path)?.try_for_each_ref(|term| {
+            add(NodeType::Protein, names::node(term.protein_id))?;
+            if let Some(term_name) = names::enrichment_term(term.term) {
+                add(NodeType::OntologyTerm, term_name)?;
+            }
+            Ok(())
+        })?;
+        Ok(SourceReport::default())
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        let mut predicates = CategoryPredicates::default();
+        Records::<StringEnrichmentTerm>::open(&self.path)?
+            .try_for_each_ref(|term| Self::term_arcs(term, &mut predicates, ids, push))?;
+        Ok(SourceReport::default())
+    }
+
+    fn par_arcs(
+        &self,
+        ids: &dyn SharedNodeIds,
+        pushes: &mut [Box<SendPushArc>],
+    ) -> Result<SourceReport> {
+        let mut workers = pushes
+            .iter_mut()
+            .map(|push| (push, CategoryPredicates::default()))
+            .collect::<Vec<_>>();
+        par_for_each::<StringEnrichmentTerm, _, _>(
+            &self.path,
+            &mut workers,
+            |(push, predicates), term| {
+                Self::term_arcs(term, predicates, &mut { ids }, &mut ***push)
+            },
+        )?;
+        Ok(SourceReport::default())
+    }
+
+    fn attr_columns(&self) -> Vec<AttrColumn> {
+        vec![AttrColumn::text("string_description")]
+    }
+
+    fn node_attrs(&self, ids: &mut dyn NodeIds, push: &mut PushAttr) -> Result<SourceReport> {
+        // the terms are few, while each has a line per protein
+        let mut seen = HashSet::new();
+        Records::<StringEnrichmentTerm>::open(&self.path)?.try_for_each_ref(|term| {
+            let Some(term_name) = names::enrichment_term_ref(term.term) else {
+                return Ok(());
+            };
+            if seen.contains(term_name.as_ref()) {
+                return Ok(());
+            }
+            seen.insert(term_name.to_string());
+            let term_id = ids.get(NodeType::OntologyTerm, &term_name)?;
+            push(
+                term_id,
+                "string_description",
+                AttrValue::Text(term.description.trim()),
+            )
+        })?;
+        Ok(SourceReport::default())
+    }
+}
+
+/// How many STRING links a pass kept and discarded.
+#[derive(Debug, Clone)]
+pub struct LinksCounts {
+    pub kept: usize,
+    pub discarded: usize,
+    /// How the links were selected, e.g. `min_score 700`
+    pub selection: String,
+}
+
+/// The name of the weight of the STRING links holding their combined score.
+pub const COMBINED_SCORE: &str = "combined_score";
+
+/// `protein.links.full.v12.0.txt.gz`: the protein-protein interactions with a
+/// combined score of at least `min_score`, or, if it is set, that pass
+/// `filter`, a [`Filter`] over the columns in [`STRING_LINK_SCORES`], which
+/// then replaces `min_score`. Its proteins are already in [`Info`], so it
+/// adds no node names.
+///
+/// Each link is weighted with the scores in `weights`, which are also names
+/// from [`STRING_LINK_SCORES`].
+pub struct Links {
+    path: PathBuf,
+    min_score: u16,
+    filter: Option<Filter>,
+    weights: Vec<String>,
+    /// The indices of `weights` in [`STRING_LINK_SCORES`]
+    weight_columns: Vec<usize>,
+}
+
+impl Links {
+    pub fn new<P: Into<PathBuf>>(
+        path: P,
+        min_score: u16,
+        filter: Option<&str>,
+        weights: Vec<String>,
+    ) -> Result<Self> {
+        let weight_columns = weights
+            .iter()
+            .map(|weight| {
+                STRING_LINK_SCORES
+                    .iter()
+                    .position(|column| column == weight)
+                    .with_context(|| {
+                        format!(
+                            "Unknown STRING link weight {}, expected one of {:?}",
+                            weight, STRING_LINK_SCORES
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // so the weights of a link fit in an array of the scores
+        ensure!(
+            weights.len() <= STRING_LINK_SCORES.len(),
+            "Too many STRING link weights {:?}, expected at most {}",
+            weights,
+            STRING_LINK_SCORES.len()
+        );
+        ensure!(
+            weights.iter().all_unique(),
+            "Duplicate STRING link weights in {:?}",
+            weights
+        );
+        Ok(Self {
+            path: path.into(),
+            min_score,
+            filter: filter
+                .map(|filter| Filter::parse(filter, &STRING_LINK_SCORES))
+                .transpose()?,
+            weights,
+            weight_columns,
+        })
+    }
+}
+
+impl Links {
+    /// Push the arc of a link if it passes the filter, or `min_score` if
+    /// there is none, returning whether it did.
+    fn link_arc(
+        &self,
+        link: StringLinkRef,
+        ids: &mut dyn NodeIds,
+        push: &mut PushArc,
+    ) -> Result<bool> {
+        let keep = match &self.filter {
+            Some(filter) => filter.eval(&link.scores),
+            None => link.combined_score() >= self.min_score,
+        };
+        if !keep {
+            return Ok(false);
+        }
+        let src_id = ids.get(NodeType::Protein, &names::node_ref(link.protein1))?;
+        let dst_id = ids.get(NodeType::Protein, &names::node_ref(link.protein2))?;
+        let mut weights = [0; STRING_LINK_SCORES.len()];
+        for (weight, &column) in weights.iter_mut().zip(&self.weight_columns) {
+            *weight = link.scores[column];
+        }
+        // this file ***SHOULD*** be already undirected
+        push(
+            src_id,
+            dst_id,
+            "interacts_with",
+            &weights[..self.weight_columns.len()],
+        )?;
+        Ok(true)
+    }
+
+    /// The report of a pass with the given counts of links.
+    fn report(&self, kept: usize, discarded: usize) -> SourceReport {
+        let selection = match &self.filter {
+            Some(filter) => format!("filter {}", filter),
+            None => format!("min_score {}", self.min_score),
+        };
+        SourceReport {
+            links: Some(LinksCounts {
+                kept,
+                discarded,
+                selection,
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+impl Source for Links {
+    fn name(&self) -> &str {
+        "string_links"
+    }
+
+    fn node_names(
+        &self,
+        _add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        Ok(SourceReport::default())
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        let mut kept = 0;
+        let mut discarded = 0;
+        Records::<StringLink>::open(&self.path)?.try_for_each_ref(|link| {
+            if self.link_arc(link, ids, push)? {
+                kept += 1;
+            } else {
+                discarded += 1;
+            }
+            Ok(())
+        })?;
+        Ok(self.report(kept, discarded))
+    }
+
+    fn par_arcs(
+        &self,
+        ids: &dyn SharedNodeIds,
+        pushes: &mut [Box<SendPushArc>],
+    ) -> Result<SourceReport> {
+        // each worker counts its kept and discarded links
+        let mut workers = pushes
+            .iter_mut()
+            .map(|push| (push, 0, 0))
+            .collect::<Vec<_>>();
+        par_for_each::<StringLink, _, _>(
+            &self.path,
+            &mut workers,
+            |(push, kept, discarded), link| {
+                if self.link_arc(link, &mut { ids }, &mut ***push)? {
+                    *kept += 1;
+                } else {
+                    *discarded += 1;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(self.report(
+            workers.iter().map(|(_, kept, _)| kept).sum(),
+            workers.iter().map(|(_, _, discarded)| discarded).sum(),
+        ))
+    }
+
+    fn weight_names(&self) -> Vec<String> {
+        self.weights.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_dir;
+    use std::fs;
+    use std::path::Path;
+
+    /// Node ids in order of first lookup.
+    #[derive(Default)]
+    struct TestIds(Vec<String>);
+
+    impl NodeIds for TestIds {
+        fn get(&mut self, _node_type: NodeType, node_name: &str) -> Result<Option<usize>> {
+            let id = match self.0.iter().position(|name| name == node_name) {
+                Some(id) => id,
+                None => {
+                    self.0.push(node_name.to_string());
+                    self.0.len() - 1
+                }
+            };
+            Ok(Some(id))
+        }
+
+        fn malformed(&mut self, _node_type: NodeType, _node_name: &str) -> Result<Option<usize>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_enrichment_terms() -> Result<()> {
+        let dir = temp_dir(std::env::temp_dir());
+        let path = Path::new(&dir).join("terms.txt");
+        fs::write(
+            &path,
+            "#string_protein_id\tcategory\tterm\tdescription
+9606.A\tBiological Process (Gene Ontology)\tGO:0006396\tRNA processing
+9606.B\tBiological Process (Gene Ontology)\tGO:0006396\tRNA processing again
+9606.A\tCellular Component (Gene Ontology)\tGO:0005634\tNucleus
+9606.A\tProtein Domains (Pfam)\tPF00870\tP53 DNA-binding domain
+",
+        )?;
+        let terms = EnrichmentTerms::new(&path);
+        let mut ids = TestIds::default();
+        let mut arcs = Vec::new();
+        terms.arcs(&mut ids, &mut |src, dst, predicate, _| {
+            arcs.push((src.unwrap(), dst.unwrap(), predicate.to_string()));
+            Ok(())
+        })?;
+        let mut attrs = Vec::new();
+        terms.node_attrs(&mut ids, &mut |id, name, value| {
+            let AttrValue::Text(text) = value else {
+                panic!("Unexpected {:?}", value);
+            };
+            attrs.push((id.unwrap(), name.to_string(), text.to_string()));
+            Ok(())
+        })?;
+        fs::remove_dir_all(&dir)?;
+
+        assert_eq!(ids.0, ["9606.A", "GO:0006396", "9606.B", "GO:0005634"]);
+        let arc = |src, dst, predicate: &str| (src, dst, predicate.to_string());
+        assert_eq!(
+            arcs,
+            [
+                arc(0, 1, "annotated_with_biological_process"),
+                arc(1, 0, "annotates_biological_process"),
+                arc(2, 1, "annotated_with_biological_process"),
+                arc(1, 2, "annotates_biological_process"),
+                arc(0, 3, "annotated_with_cellular_component"),
+                arc(3, 0, "annotates_cellular_component"),
+            ]
+        );
+        let attr = |id, text: &str| (id, "string_description".to_string(), text.to_string());
+        assert_eq!(attrs, [attr(1, "RNA processing"), attr(3, "Nucleus")]);
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! The UniProtKB entries, e.g. the reviewed ones of Swiss-Prot.
+
+use super::{NodeIds, PushArc, PushAttr, Source, SourceReport};
+use crate::attrs::{AttrColumn, AttrValue};
+use crate::names;
+use crate::node_type::NodeType;
+use crate::readers::{UniprotEntries, UniprotEntry};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// `uniprot_sprot.dat.gz`: each entry is a node named after its primary
+/// accession, with arcs in both directions to the NCBI taxon of its
+/// organism, to its cross-references in the databases of `xrefs`, e.g. GO,
+/// InterPro and Pfam, and to its secondary accessions, and an arc to the
+/// node of its review status, see [`names::uniprot_status`]. The
+/// description, gene names and keywords, both joined by `|`, organism and
+/// sequence length of each entry are attributes of its primary accession.
+pub struct Entries {
+    path: PathBuf,
+    /// The databases of the `DR` lines that become arcs
+    xrefs: Vec<String>,
+}
+
+impl Entries {
+    pub fn new<P: Into<PathBuf>>(path: P, xrefs: Vec<String>) -> Self {
+        Self {
+            path: path.into(),
+            xrefs,
+        }
+    }
+
+    /// The cross-references of an entry to the databases of `xrefs`, as
+    /// node names with their types.
+    fn xref_names<'a>(
+        &'a self,
+        entry: &'a UniprotEntry,
+    ) -> impl Iterator<Item = (NodeType, String)> + 'a {
+        entry
+            .xrefs
+            .iter()
+            .filter(|xref| {
+                self.xrefs
+                    .iter()
+                    .any(|database| database.eq_ignore_ascii_case(&xref.database))
+            })
+            .map(|xref| {
+                (
+                    xref_type(&xref.database),
+                    names::uniprot_xref(&xref.database, &xref.id),
+                )
+            })
+    }
+}
+
+/// The type of the nodes of the cross-references to a database: the
+/// InterPro and Pfam entries are protein families, and the other databases,
+/// e.g. GO, are ontologies.
+fn xref_type(database: &str) -> NodeType {
+    if ["InterPro", "Pfam"]
+        .iter()
+        .any(|family| family.eq_ignore_ascii_case(database))
+    {
+        NodeType::ProteinFamily
+    } else {
+        NodeType::OntologyTerm
+    }
+}
+
+impl Source for Entries {
+    fn name(&self) -> &str {
+        "uniprot"
+    }
+
+    fn node_names(
+        &self,
+        add: &mut dyn FnMut(NodeType, String) -> Result<()>,
+    ) -> Result<SourceReport> {
+        for entry in UniprotEntries::open(&self.path)? {
+            let entry = entry?;
+            for accession in &entry.accessions {
+                add(NodeType::Uniprot, names::node(accession))?;
+            }
+            if let Some(taxon_id) = &entry.taxon_id {
+                add(NodeType::Taxon, names::ncbi_taxon(taxon_id))?;
+            }
+            for (node_type, name) in self.xref_names(&entry) {
+                add(node_type, name)?;
+            }
+            add(
+                NodeType::Annotation,
+                names::uniprot_status(entry.reviewed).to_string(),
+            )?;
+        }
+        Ok(SourceReport::default())
+    }
+
+    fn arcs(&self, ids: &mut dyn NodeIds, push: &mut PushArc) -> Result<SourceReport> {
+        for entry in UniprotEntries::open(&self.path)? {
+            let entry = entry?;
+            let protein_id = ids.get(NodeType::Uniprot, &names::node(entry.accession()))?;
+            if let Some(taxon_id) = &entry.taxon_id {
+                let taxon_id = ids.get(NodeType::Taxon, &names::ncbi_taxon(taxon_id))?;
+                push(protein_id, taxon_id, "in_taxon", &[])?;
+                push(taxon_id, protein_id, "taxon_of", &[])?;
+            }
+            for (node_type, name) in self.xref_names(&entry) {
+                let xref_id = ids.get(node_type, &name)?;
+                push(protein_id, xref_id, "annotated_with", &[])?;
+                push(xref_id, protein_id, "annotates", &[])?;
+            }
+            for accession in entry.secondary_accessions() {
+                let alias_id = ids.get(NodeType::Uniprot, &names::node(accession))?;
+                push(protein_id, alias_id, "has_alias", &[])?;
+                push(alias_id, protein_id, "alias_of", &[])?;
+            }
+            let status_id = ids.get(NodeType::Annotation, names::uniprot_status(entry.reviewed))?;
+            push(protein_id, status_id, "has_status", &[])?;
+        }
+        Ok(SourceReport::default())
+    }
+
+    fn attr_columns(&self) -> Vec<AttrColumn> {
+        vec![
+            AttrColumn::text("description"),
+            AttrColumn::text("gene_names"),
+            AttrColumn::text("keywords"),
+            AttrColumn::text("organism"),
+            AttrColumn::int("protein_size"),
+        ]
+    }
+
+    fn node_attrs(&self, ids: &mut dyn NodeIds, push: &mut PushAttr) -> Result<SourceReport> {
+        for entry in UniprotEntries::open(&self.path)? {
+            let entry = entry?;
+            let protein_id = ids.get(NodeType::Uniprot, &names::node(entry.accession()))?;
+            if let Some(description) = &entry.description {
+                push(protein_id, "description", AttrValue::Text(description))?;
+            }
+            if !entry.gene_names.is_empty() {
+                let gene_names = entry.gene_names.join("|");
+                push(protein_id, "gene_names", AttrValue::Text(&gene_names))?;
+            }
+            if !entry.keywords.is_empty() {
+                let keywords = entry.keywords.join("|");
+                push(protein_id, "keywords", AttrValue::Text(&keywords))?;
+            }
+            push(protein_id, "organism", AttrValue::Text(&entry.organism))?;
+            push(
+                protein_id,
+                "protein_size",
+                AttrValue::Int(entry.length as u64),
+            )?;
+        }
+        Ok(SourceReport::default())
+    }
+}
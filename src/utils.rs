@@ -0,0 +1,86 @@
+//! Small helpers shared by the binaries.
+
+use anyhow::{Context, Result};
+use memmap2::Mmap;
+use rand::Rng;
+use std::fs;
+use std::path::Path;
+
+/// Given a float, return it in a human readable format using SI suffixes.
+pub fn humanize_float(mut x: f64) -> (f64, &'static str) {
+    const UOM: &[&str] = &[
+        "q", "r", "y", "z", "a", "f", "p", "n", "μ", "m", "", "K", "M", "G", "T", "P", "E", "Z",
+        "Y", "R", "Q",
+    ];
+    let mut uom_idx = 10;
+    debug_assert_eq!(UOM[uom_idx], "");
+
+    if x.abs() > 1.0 {
+        while x.abs() > 1000.0 {
+            uom_idx += 1;
+            x /= 1000.0;
+        }
+    } else {
+        while x.abs() < 0.001 {
+            uom_idx -= 1;
+            x *= 1000.0;
+        }
+    }
+
+    (x, UOM[uom_idx])
+}
+
+/// Create a new random dir inside the given folder
+pub fn temp_dir<P: AsRef<Path>>(base: P) -> String {
+    let mut base = base.as_ref().to_owned();
+    const ALPHABET: &[u8] = b"0123456789abcdef";
+    let mut rnd = rand::thread_rng();
+    let mut random_str = String::new();
+    loop {
+        random_str.clear();
+        for _ in 0..16 {
+            let idx = rnd.gen_range(0..ALPHABET.len());
+            random_str.push(ALPHABET[idx] as char);
+        }
+        base.push(&random_str);
+
+        if !base.exists() {
+            std::fs::create_dir(&base).unwrap();
+            return base.to_string_lossy().to_string();
+        }
+        base.pop();
+    }
+}
+
+/// Memory-map a whole file read-only.
+pub fn mmap<P: AsRef<Path>>(path: P) -> Result<Mmap> {
+    let path = path.as_ref();
+    let file =
+        fs::File::open(path).with_context(|| format!("Could not open {}", path.display()))?;
+    // SAFETY: the files we map are never modified after being written
+    unsafe { Mmap::map(&file) }.with_context(|| format!("Could not mmap {}", path.display()))
+}
+
+/// Append `x` to `buffer` as a LEB128 varint.
+pub(crate) fn write_varint(buffer: &mut Vec<u8>, mut x: usize) {
+    while x >= 0x80 {
+        buffer.push((x as u8) | 0x80);
+        x >>= 7;
+    }
+    buffer.push(x as u8);
+}
+
+/// Read a LEB128 varint from `data` starting at `pos`, advancing it.
+pub(crate) fn read_varint(data: &[u8], pos: &mut usize) -> usize {
+    let mut x = 0;
+    let mut shift = 0;
+    loop {
+        let byte = data[*pos];
+        *pos += 1;
+        x |= ((byte & 0x7f) as usize) << shift;
+        if byte < 0x80 {
+            return x;
+        }
+        shift += 7;
+    }
+}
@@ -0,0 +1,667 @@
+//! The vocabulary that maps node names to node ids and back.
+//!
+//! The vocabulary is built by [`VocabBuilder`], which sorts and deduplicates
+//! the node names on disk, so the ids do not depend on the order in which
+//! the sources are scanned nor on the order of the lines of their files: two
+//! builds from the same inputs produce the same files. How ids are assigned
+//! is given by the [`IdPolicy`] recorded in the metadata. The vocabulary is
+//! stored as a set of files sharing a basename:
+//! - `{basename}.names` and `{basename}.names.offsets`: the [`NameStore`]
+//!   with the names in id order;
+//! - `{basename}.mph`: a minimal perfect hash function on the names;
+//! - `{basename}.mph.ids`: for each value of the hash function the id of the
+//!   corresponding name, as a little-endian `u32`;
+//! - `{basename}.json`: the [`VocabMetadata`], including with
+//!   [`IdPolicy::ByType`] the [`TypeRange`] of the ids of each node type;
+//! - `{basename}.tsv`: the name of each node at the line with the
+//!   corresponding index, for other tools.
+//!
+//! [`Vocab::load`] memory-maps everything but the hash function, which needs
+//! about 3 bits per name, so neither the build nor the lookups materialize
+//! all the names on the heap.
+
+use crate::name_store::{NameStore, NameStoreWriter, BUCKET_SIZE};
+use crate::names::Case;
+use crate::node_type::NodeType;
+use crate::sources::NodeIds;
+use crate::utils::{humanize_float, mmap, temp_dir};
+use anyhow::{bail, ensure, Context, Result};
+use dsi_progress_logger::*;
+use memmap2::{Mmap, MmapMut};
+use ph::fmph;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// How node ids are assigned to the node names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdPolicy {
+    /// The ids are the ranks of the names in lexicographic (byte) order.
+    Lexicographic,
+    /// The ids are grouped by [`NodeType`], in the order of
+    /// [`NodeType::ALL`], and are in lexicographic order within each group.
+    /// A name added with more than one type gets the first one.
+    #[default]
+    ByType,
+}
+
+/// The contiguous range of the ids of a node type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeRange {
+    pub node_type: NodeType,
+    /// The first id of the type
+    pub first_id: usize,
+    /// The number of ids of the type
+    pub count: usize,
+}
+
+impl TypeRange {
+    /// The ids of the type.
+    pub fn ids(&self) -> Range<usize> {
+        self.first_id..self.first_id + self.count
+    }
+}
+
+/// The metadata of a vocabulary, stored in `{basename}.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VocabMetadata {
+    /// The number of nodes
+    pub num_nodes: usize,
+    /// How many names are front-coded together
+    pub bucket_size: usize,
+    /// How the ids were assigned
+    pub id_policy: IdPolicy,
+    /// How the keys are derived from the names
+    pub case: Case,
+    /// Whether the names as given are stored in `{basename}.display.names`
+    pub display_names: bool,
+    /// How many names were added with more than one type
+    pub type_conflicts: usize,
+    /// With [`IdPolicy::ByType`], the id range of each type in the order of
+    /// [`NodeType::ALL`]; empty otherwise, as the types are interleaved
+    pub node_types: Vec<TypeRange>,
+}
+
+impl VocabMetadata {
+    /// Load the metadata of the vocabulary with the given basename.
+    pub fn load<P: AsRef<Path>>(basename: P) -> Result<Self> {
+        let path = format!("{}.json", basename.as_ref().display());
+        let metadata: Self = serde_json::from_reader(io::BufReader::new(
+            fs::File::open(&path).with_context(|| format!("Could not open {}", path))?,
+        ))
+        .with_context(|| format!("Could not parse {}", path))?;
+        ensure!(
+            metadata.bucket_size == BUCKET_SIZE,
+            "Unsupported bucket size {}",
+            metadata.bucket_size
+        );
+        Ok(metadata)
+    }
+
+    /// The type of a node id, if the ids are grouped by type.
+    pub fn node_type(&self, node_id: usize) -> Option<NodeType> {
+        self.node_types
+            .iter()
+            .find(|range| range.ids().contains(&node_id))
+            .map(|range| range.node_type)
+    }
+
+    /// The ids of a node type, if the ids are grouped by type.
+    pub fn type_range(&self, node_type: NodeType) -> Option<Range<usize>> {
+        self.node_types
+            .iter()
+            .find(|range| range.node_type == node_type)
+            .map(TypeRange::ids)
+    }
+}
+
+/// Read the `index`-th little-endian `u32` of `data`.
+fn read_u32(data: &[u8], index: usize) -> u32 {
+    u32::from_le_bytes(data[index * 4..index * 4 + 4].try_into().unwrap())
+}
+
+/// A key with its type and display name, which is empty if it is the key.
+type Entry = (String, NodeType, String);
+
+/// Parse a line of a sorted chunk, i.e. the index of the type, the key and
+/// the display name.
+fn parse_chunk_line(line: &str) -> Result<Entry> {
+    let mut fields = line.splitn(3, '\t');
+    let (Some(node_type), Some(key), Some(display)) = (fields.next(), fields.next(), fields.next())
+    else {
+        bail!("Invalid chunk line {:?}", line);
+    };
+    let node_type = NodeType::from_index(node_type.parse()?)
+        .with_context(|| format!("Invalid node type in chunk line {:?}", line))?;
+    Ok((key.to_string(), node_type, display.to_string()))
+}
+
+/// The files written while merging the names, in id order.
+struct NamesWriter {
+    keys: NameStoreWriter,
+    display: Option<NameStoreWriter>,
+    tsv: io::BufWriter<fs::File>,
+}
+
+impl NamesWriter {
+    fn push(&mut self, key: &str, display: &str) -> Result<()> {
+        self.keys.push(key.as_bytes())?;
+        if let Some(writer) = &mut self.display {
+            writer.push(display.as_bytes())?;
+        }
+        writeln!(self.tsv, "{}", key)?;
+        Ok(())
+    }
+}
+
+/// Builds a vocabulary from node names given in any order and with
+/// repetitions, using a bounded amount of memory.
+///
+/// Names are kept in memory until there are `batch_size` of them, then they
+/// are sorted, deduplicated and dumped to a file in a temporary directory;
+/// at the end all the files are merged, and the ids are assigned following
+/// the [`IdPolicy`]. The names are stored as keys following the [`Case`]
+/// policy, and optionally also as given, as display names.
+pub struct VocabBuilder {
+    dir: PathBuf,
+    batch_size: usize,
+    id_policy: IdPolicy,
+    case: Case,
+    display_names: bool,
+    batch: Vec<Entry>,
+    chunks: Vec<PathBuf>,
+}
+
+impl VocabBuilder {
+    /// A new builder that writes its temporary files in a new directory
+    /// inside `tmp_dir`.
+    pub fn new<P: AsRef<Path>>(tmp_dir: P, batch_size: usize, id_policy: IdPolicy) -> Self {
+        Self {
+            dir: PathBuf::from(temp_dir(tmp_dir)),
+            batch_size,
+            id_policy,
+            case: Case::default(),
+            display_names: false,
+            batch: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Set how the keys are derived from the names.
+    pub fn with_case(mut self, case: Case) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Set whether to store the names as given, as display names. If a key
+    /// is added with different names, the display name is the smallest one.
+    pub fn with_display_names(mut self, display_names: bool) -> Self {
+        self.display_names = display_names;
+        self
+    }
+
+    /// Add a node name with its type.
+    pub fn add(&mut self, node_type: NodeType, node_name: String) -> Result<()> {
+        ensure!(
+            !node_name.contains(['\t', '\n']),
+            "Node name {:?} contains a tab or a newline",
+            node_name
+        );
+        let key = self.case.key(&node_name).into_owned();
+        let display = if self.display_names && key != node_name {
+            node_name
+        } else {
+            String::new()
+        };
+        self.batch.push((key, node_type, display));
+        if self.batch.len() >= self.batch_size {
+            self.dump_batch()?;
+        }
+        Ok(())
+    }
+
+    fn dump_batch(&mut self) -> Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        self.batch.sort_unstable();
+        self.batch.dedup();
+        let path = self.dir.join(format!("{}.names", self.chunks.len()));
+        let mut file = io::BufWriter::new(fs::File::create(&path)?);
+        for (key, node_type, display) in self.batch.drain(..) {
+            writeln!(file, "{}\t{}\t{}", node_type.index(), key, display)?;
+        }
+        file.flush()?;
+        self.chunks.push(path);
+        Ok(())
+    }
+
+    /// Merge the names and write the vocabulary files with the given
+    /// basename, returning their metadata.
+    pub fn build<P: AsRef<Path>>(mut self, basename: P) -> Result<VocabMetadata> {
+        let basename = basename.as_ref().to_string_lossy().to_string();
+        self.dump_batch()?;
+
+        let mut pl = ProgressLogger::default();
+        pl.display_memory(true);
+        pl.start("Merging the node names");
+        let mut writer = NamesWriter {
+            keys: NameStoreWriter::new(&basename)?,
+            display: if self.display_names {
+                Some(NameStoreWriter::new(format!("{}.display", basename))?)
+            } else {
+                None
+            },
+            tsv: io::BufWriter::new(fs::File::create(format!("{}.tsv", basename))?),
+        };
+        // with IdPolicy::ByType the merged names are first split by type
+        let mut groups = match self.id_policy {
+            IdPolicy::Lexicographic => Vec::new(),
+            IdPolicy::ByType => NodeType::ALL
+                .iter()
+                .map(|node_type| {
+                    let path = self.dir.join(format!("{}.group", node_type));
+                    Ok((io::BufWriter::new(fs::File::create(&path)?), path))
+                })
+                .collect::<Result<Vec<_>>>()?,
+        };
+        let mut chunks = self
+            .chunks
+            .iter()
+            .map(|path| Ok(io::BufReader::new(fs::File::open(path)?).lines()))
+            .collect::<Result<Vec<_>>>()?;
+        let mut heap = BinaryHeap::new();
+        for (chunk_id, chunk) in chunks.iter_mut().enumerate() {
+            if let Some(line) = chunk.next() {
+                heap.push(Reverse((parse_chunk_line(&line?)?, chunk_id)));
+            }
+        }
+        let mut previous: Option<(String, NodeType)> = None;
+        let mut conflicting = false;
+        let mut type_conflicts = 0;
+        while let Some(Reverse(((key, node_type, display), chunk_id))) = heap.pop() {
+            if let Some(line) = chunks[chunk_id].next() {
+                heap.push(Reverse((parse_chunk_line(&line?)?, chunk_id)));
+            }
+            // the first occurrence of a key has the first of its types
+            if let Some((previous_key, previous_type)) = &previous {
+                if *previous_key == key {
+                    if *previous_type != node_type && !conflicting {
+                        conflicting = true;
+                        type_conflicts += 1;
+                    }
+                    continue;
+                }
+            }
+            conflicting = false;
+            let display = if display.is_empty() { &key } else { &display };
+            match self.id_policy {
+                IdPolicy::Lexicographic => writer.push(&key, display)?,
+                IdPolicy::ByType => writeln!(groups[node_type.index()].0, "{}\t{}", key, display)?,
+            }
+            previous = Some((key, node_type));
+            pl.light_update();
+        }
+        let mut node_types = Vec::new();
+        for ((mut group, path), node_type) in groups.into_iter().zip(NodeType::ALL) {
+            group.flush()?;
+            drop(group);
+            let first_id = writer.keys.len();
+            for line in io::BufReader::new(fs::File::open(path)?).lines() {
+                let line = line?;
+                let (key, display) = line
+                    .split_once('\t')
+                    .with_context(|| format!("Invalid group line {:?}", line))?;
+                writer.push(key, display)?;
+            }
+            node_types.push(TypeRange {
+                node_type,
+                first_id,
+                count: writer.keys.len() - first_id,
+            });
+        }
+        let num_nodes = writer.keys.finish()?;
+        if let Some(display) = writer.display {
+            display.finish()?;
+        }
+        writer.tsv.flush()?;
+        pl.done();
+        fs::remove_dir_all(&self.dir)?;
+        ensure!(
+            num_nodes <= u32::MAX as usize,
+            "Too many nodes for 32-bit ids: {}",
+            num_nodes
+        );
+
+        let mut pl = ProgressLogger::default();
+        pl.display_memory(true);
+        pl.start("Building the minimal perfect hash function");
+        let names = NameStore::load_with_len(&basename, num_nodes)?;
+        let mph = fmph::Function::new(fmph::keyset::CachedKeySet::dynamic_with_len(
+            || names.iter(),
+            num_nodes,
+            1_000_000,
+        ));
+        mph.write(&mut io::BufWriter::new(fs::File::create(format!(
+            "{}.mph",
+            basename
+        ))?))?;
+        pl.done();
+
+        let mut pl = ProgressLogger::default();
+        pl.display_memory(true);
+        pl.start("Mapping the hash values to node ids");
+        let ids_file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(format!("{}.mph.ids", basename))?;
+        ids_file.set_len(num_nodes as u64 * 4)?;
+        // SAFETY: we just created the file and nobody else uses it
+        let mut ids = unsafe { MmapMut::map_mut(&ids_file)? };
+        for (node_id, node_name) in names.iter().enumerate() {
+            let slot = mph.get(&node_name).unwrap() as usize;
+            ids[slot * 4..slot * 4 + 4].copy_from_slice(&(node_id as u32).to_le_bytes());
+            pl.light_update();
+        }
+        ids.flush()?;
+        pl.done();
+
+        let metadata = VocabMetadata {
+            num_nodes,
+            bucket_size: BUCKET_SIZE,
+            id_policy: self.id_policy,
+            case: self.case,
+            display_names: self.display_names,
+            type_conflicts,
+            node_types,
+        };
+        serde_json::to_writer_pretty(fs::File::create(format!("{}.json", basename))?, &metadata)?;
+        Ok(metadata)
+    }
+}
+
+/// With [`crate::missing::MissingPolicy::AutoAdd`], the endpoints of the arcs
+/// are added to the builder. The ids are not known yet, so the arcs get
+/// `None` endpoints.
+impl NodeIds for VocabBuilder {
+    fn get(&mut self, node_type: NodeType, node_name: &str) -> Result<Option<usize>> {
+        self.add(node_type, node_name.to_string())?;
+        Ok(None)
+    }
+
+    fn malformed(&mut self, _node_type: NodeType, _node_name: &str) -> Result<Option<usize>> {
+        Ok(None)
+    }
+}
+
+/// A vocabulary built by [`VocabBuilder`].
+pub struct Vocab {
+    metadata: VocabMetadata,
+    mph: fmph::Function,
+    ids: Mmap,
+    names: NameStore,
+    display: Option<NameStore>,
+}
+
+impl Vocab {
+    /// Load the vocabulary with the given basename.
+    pub fn load<P: AsRef<Path>>(basename: P) -> Result<Self> {
+        let basename = basename.as_ref().to_string_lossy().to_string();
+        let metadata = VocabMetadata::load(&basename)?;
+        let path = format!("{}.mph", basename);
+        let mph = fmph::Function::read(&mut io::BufReader::new(
+            fs::File::open(&path).with_context(|| format!("Could not open {}", path))?,
+        ))?;
+        let ids = mmap(format!("{}.mph.ids", basename))?;
+        ensure!(
+            ids.len() == metadata.num_nodes * 4,
+            "{}.mph.ids does not match the number of nodes",
+            basename
+        );
+        let names = NameStore::load_with_len(&basename, metadata.num_nodes)?;
+        let display = if metadata.display_names {
+            Some(NameStore::load_with_len(
+                format!("{}.display", basename),
+                metadata.num_nodes,
+            )?)
+        } else {
+            None
+        };
+        Ok(Self {
+            metadata,
+            mph,
+            ids,
+            names,
+            display,
+        })
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.metadata.num_nodes
+    }
+
+    /// The metadata of the vocabulary.
+    pub fn metadata(&self) -> &VocabMetadata {
+        &self.metadata
+    }
+
+    /// The type of a node id, if the ids are grouped by type, see
+    /// [`VocabMetadata::node_type`].
+    pub fn node_type(&self, node_id: usize) -> Option<NodeType> {
+        self.metadata.node_type(node_id)
+    }
+
+    /// Whether the vocabulary is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The id of a node name, if it is in the vocabulary. The name is
+    /// mapped to a key with the [`Case`] policy of the vocabulary; the key is
+    /// built on the stack and compared with the stored one while it is
+    /// decoded, so a lookup does not allocate.
+    pub fn id_of(&self, node_name: &str) -> Option<usize> {
+        self.metadata.case.with_key(node_name, |key| {
+            let slot = self.mph.get(key)? as usize;
+            if slot >= self.len() {
+                return None;
+            }
+            let node_id = read_u32(&self.ids, slot) as usize;
+            // the hash function maps names not in the vocabulary to random slots
+            self.names
+                .matches(node_id, key.as_bytes())
+                .then_some(node_id)
+        })
+    }
+
+    /// The key of a node id, if it is in the vocabulary.
+    pub fn name_of(&self, node_id: usize) -> Option<String> {
+        self.names.get(node_id)
+    }
+
+    /// The display name of a node id, if it is in the vocabulary, or its key
+    /// if the vocabulary has no display names.
+    pub fn display_name_of(&self, node_id: usize) -> Option<String> {
+        self.display.as_ref().unwrap_or(&self.names).get(node_id)
+    }
+
+    /// The store of the node names.
+    pub fn names(&self) -> &NameStore {
+        &self.names
+    }
+
+    /// Iterate on the node names in id order.
+    pub fn iter(&self) -> impl Iterator<Item = String> + '_ {
+        self.names.iter()
+    }
+}
+
+/// Get the id of a node name, failing if it is not in the vocabulary.
+pub fn node_id(vocab: &Vocab, node_name: &str) -> Result<usize> {
+    vocab
+        .id_of(node_name)
+        .with_context(|| format!("Could not map {}", node_name))
+}
+
+/// Print the size of the vocabulary.
+pub fn print_vocab(num_nodes: usize) {
+    let (v, uom) = humanize_float(num_nodes as f64);
+    println!("vocab size: {:.3}{}, {}", v, uom, num_nodes);
+}
+
+/// Print the type table of the vocabulary, one type per line.
+pub fn print_node_types(metadata: &VocabMetadata) {
+    for range in &metadata.node_types {
+        println!("{}\t{}\t{}", range.node_type, range.first_id, range.count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a vocabulary from the given names, with a tiny batch so that
+    /// they are merged from several chunks, and load it. Returns the
+    /// temporary directory to remove, too.
+    fn build(
+        names: &[(NodeType, &str)],
+        id_policy: IdPolicy,
+        case: Case,
+    ) -> Result<(String, Vocab)> {
+        let dir = temp_dir(std::env::temp_dir());
+        let mut builder = VocabBuilder::new(&dir, 3, id_policy)
+            .with_case(case)
+            .with_display_names(true);
+        for (node_type, node_name) in names {
+            builder.add(*node_type, node_name.to_string())?;
+        }
+        let basename = Path::new(&dir).join("vocab");
+        builder.build(&basename)?;
+        let vocab = Vocab::load(&basename)?;
+        Ok((dir, vocab))
+    }
+
+    /// Check that the hash function maps every name to its id.
+    fn check_ids(vocab: &Vocab) {
+        for (node_id, node_name) in vocab.iter().enumerate() {
+            assert_eq!(vocab.id_of(&node_name), Some(node_id));
+            assert_eq!(vocab.name_of(node_id), Some(node_name));
+        }
+        assert_eq!(vocab.name_of(vocab.len()), None);
+    }
+
+    #[test]
+    fn test_merge() -> Result<()> {
+        let names = ["D", "B", "A", "E", "B", "C", "A", "F", "D", "G", "A"];
+        let (dir, vocab) = build(
+            &names.map(|name| (NodeType::Protein, name)),
+            IdPolicy::Lexicographic,
+            Case::Preserve,
+        )?;
+        // sorted and deduplicated across the chunks
+        assert_eq!(
+            vocab.iter().collect::<Vec<_>>(),
+            ["A", "B", "C", "D", "E", "F", "G"]
+        );
+        assert!(vocab.metadata().node_types.is_empty());
+        check_ids(&vocab);
+        assert_eq!(vocab.id_of("H"), None);
+        assert_eq!(vocab.id_of(""), None);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_by_type() -> Result<()> {
+        let (dir, vocab) = build(
+            &[
+                (NodeType::Protein, "9606.P2"),
+                (NodeType::Taxon, "NCBITaxon:9606"),
+                (NodeType::OmaGroup, "OMA:1"),
+                (NodeType::Protein, "9606.P1"),
+                (NodeType::Taxon, "NCBITaxon:10090"),
+                (NodeType::Protein, "9606.P2"),
+                // the same name with another type keeps the first of them
+                (NodeType::Alias, "NCBITaxon:9606"),
+            ],
+            IdPolicy::ByType,
+            Case::Preserve,
+        )?;
+        assert_eq!(
+            vocab.iter().collect::<Vec<_>>(),
+            [
+                "NCBITaxon:10090",
+                "NCBITaxon:9606",
+                "9606.P1",
+                "9606.P2",
+                "OMA:1"
+            ]
+        );
+        let metadata = vocab.metadata();
+        assert_eq!(metadata.type_conflicts, 1);
+        assert_eq!(metadata.node_types.len(), NodeType::ALL.len());
+        // the ranges are contiguous and cover all the ids
+        let mut next_id = 0;
+        for range in &metadata.node_types {
+            assert_eq!(range.first_id, next_id);
+            next_id += range.count;
+        }
+        assert_eq!(next_id, vocab.len());
+        assert_eq!(metadata.type_range(NodeType::Taxon), Some(0..2));
+        assert_eq!(metadata.type_range(NodeType::Protein), Some(2..4));
+        assert_eq!(metadata.type_range(NodeType::OmaGroup), Some(4..5));
+        assert_eq!(metadata.type_range(NodeType::Alias), Some(5..5));
+        assert_eq!(vocab.node_type(3), Some(NodeType::Protein));
+        assert_eq!(vocab.node_type(5), None);
+        check_ids(&vocab);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_case() -> Result<()> {
+        let names = ["GO:0000001", "go:0000001", "Protein_Kinase", "Straße"];
+        let names = names.map(|name| (NodeType::OntologyTerm, name));
+        let long = "x".repeat(200);
+
+        let (dir, vocab) = build(&names, IdPolicy::Lexicographic, Case::Upper)?;
+        assert_eq!(vocab.len(), 3);
+        assert_eq!(vocab.id_of("go:0000001"), vocab.id_of("GO:0000001"));
+        assert!(vocab.id_of("protein_kinase").is_some());
+        assert_eq!(vocab.id_of("straße"), vocab.id_of("STRASSE"));
+        assert!(vocab.id_of("STRASSE").is_some());
+        assert_eq!(vocab.id_of(&long), None);
+        // the display name is the smallest name with the key
+        let node_id = vocab.id_of("PROTEIN_KINASE").unwrap();
+        assert_eq!(vocab.display_name_of(node_id).unwrap(), "Protein_Kinase");
+        check_ids(&vocab);
+        fs::remove_dir_all(&dir)?;
+
+        let (dir, vocab) = build(&names, IdPolicy::Lexicographic, Case::Lower)?;
+        assert_eq!(vocab.len(), 3);
+        assert_eq!(vocab.id_of("GO:0000001"), vocab.id_of("go:0000001"));
+        assert!(vocab.id_of("PROTEIN_KINASE").is_some());
+        assert!(vocab.id_of("STRASSE").is_none());
+        assert!(vocab.id_of("STRAßE").is_some());
+        check_ids(&vocab);
+        fs::remove_dir_all(&dir)?;
+
+        let (dir, vocab) = build(&names, IdPolicy::Lexicographic, Case::Preserve)?;
+        assert_eq!(vocab.len(), 4);
+        assert_ne!(vocab.id_of("GO:0000001"), vocab.id_of("go:0000001"));
+        assert!(vocab.id_of("protein_kinase").is_none());
+        assert!(vocab.id_of("Straße").is_some());
+        check_ids(&vocab);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+//! The weights of the arcs of a source, e.g. the scores of the STRING links.
+//!
+//! A source with [`crate::sources::Source::weight_names`] pushes with every
+//! arc one value for each of its weights. The weighted arcs are sorted on
+//! disk by [`WeightsBuilder`] and stored next to the graph, with basename
+//! `{output}.{source}`:
+//! - `{basename}.weights` and `{basename}.weights.offsets`: the weights of
+//!   each arc, in the format of [`crate::arc_values`];
+//! - `{basename}.weights.json`: the [`WeightsMetadata`], with the names of
+//!   the weights.
+//!
+//! If an arc is pushed more than once, each of its weights is the largest
+//! one. [`ArcWeights`] gives the weights of an arc and the successors of a
+//! node above a threshold, so thresholds can be chosen at query time.
+
+use crate::arc_values::{ArcValues, ArcValuesWriter, Successors};
+use crate::utils::temp_dir;
+use anyhow::{ensure, Context, Result};
+use dsi_progress_logger::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// The metadata of the weights, stored in `{basename}.weights.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightsMetadata {
+    /// The number of nodes
+    pub num_nodes: usize,
+    /// The number of weighted arcs
+    pub num_arcs: usize,
+    /// The names of the weights of each arc, in order
+    pub columns: Vec<String>,
+}
+
+/// A sorted chunk of weighted arcs, each stored as the source and the
+/// successor as little-endian `u32`s followed by the weights as
+/// little-endian `u16`s.
+struct Chunk {
+    file: io::BufReader<fs::File>,
+    record: Vec<u8>,
+}
+
+impl Chunk {
+    /// Read the next arc and its weights, if any.
+    fn next(&mut self, weights: &mut [u16]) -> Result<Option<(usize, usize)>> {
+        match self.file.read_exact(&mut self.record) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let src = u32::from_le_bytes(self.record[0..4].try_into().unwrap()) as usize;
+        let dst = u32::from_le_bytes(self.record[4..8].try_into().unwrap()) as usize;
+        for (i, weight) in weights.iter_mut().enumerate() {
+            *weight = u16::from_le_bytes(self.record[8 + 2 * i..10 + 2 * i].try_into().unwrap());
+        }
+        Ok(Some((src, dst)))
+    }
+}
+
+/// Sorts weighted arcs given in any order using a bounded amount of memory,
+/// like [`crate::vocab::VocabBuilder`] does with the node names.
+pub struct WeightsBuilder {
+    dir: PathBuf,
+    /// The directories of the builders merged with [`WeightsBuilder::append`]
+    other_dirs: Vec<PathBuf>,
+    batch_size: usize,
+    columns: Vec<String>,
+    arcs: Vec<(usize, usize)>,
+    weights: Vec<u16>,
+    chunks: Vec<PathBuf>,
+}
+
+impl WeightsBuilder {
+    /// A new builder for arcs with the given weights, which writes its
+    /// temporary files in a new directory inside `tmp_dir`.
+    pub fn new<P: AsRef<Path>>(tmp_dir: P, batch_size: usize, columns: Vec<String>) -> Self {
+        Self {
+            dir: PathBuf::from(temp_dir(tmp_dir)),
+            other_dirs: Vec::new(),
+            batch_size,
+            columns,
+            arcs: Vec::new(),
+            weights: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Add an arc with its weights, one for each column.
+    pub fn push(&mut self, src: usize, dst: usize, weights: &[u16]) -> Result<()> {
+        ensure!(
+            weights.len() == self.columns.len(),
+            "Expected {} weights, got {}",
+            self.columns.len(),
+            weights.len()
+        );
+        self.arcs.push((src, dst));
+        self.weights.extend_from_slice(weights);
+        if self.arcs.len() >= self.batch_size {
+            self.dump_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Add the arcs of another builder with the same weights, e.g. one
+    /// filled by another thread.
+    pub fn append(&mut self, mut other: WeightsBuilder) -> Result<()> {
+        ensure!(
+            other.columns == self.columns,
+            "Cannot merge weights {:?} with weights {:?}",
+            other.columns,
+            self.columns
+        );
+        other.dump_batch()?;
+        self.chunks.append(&mut other.chunks);
+        self.other_dirs.push(other.dir);
+        self.other_dirs.append(&mut other.other_dirs);
+        Ok(())
+    }
+
+    fn dump_batch(&mut self) -> Result<()> {
+        if self.arcs.is_empty() {
+            return Ok(());
+        }
+        let num_columns = self.columns.len();
+        let mut order = (0..self.arcs.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| self.arcs[i]);
+        let path = self.dir.join(format!("{}.weights", self.chunks.len()));
+        let mut file = io::BufWriter::new(fs::File::create(&path)?);
+        for i in order {
+            let (src, dst) = self.arcs[i];
+            file.write_all(&(src as u32).to_le_bytes())?;
+            file.write_all(&(dst as u32).to_le_bytes())?;
+            for weight in &self.weights[i * num_columns..(i + 1) * num_columns] {
+                file.write_all(&weight.to_le_bytes())?;
+            }
+        }
+        file.flush()?;
+        self.arcs.clear();
+        self.weights.clear();
+        self.chunks.push(path);
+        Ok(())
+    }
+
+    /// Merge the arcs and write the weights with the given basename.
+    pub fn build<P: AsRef<Path>>(
+        mut self,
+        basename: P,
+        num_nodes: usize,
+    ) -> Result<WeightsMetadata> {
+        let basename = basename.as_ref().to_string_lossy().to_string();
+        self.dump_batch()?;
+
+        let mut pl = ProgressLogger::default();
+        pl.display_memory(true);
+        pl.start(format!("Writing {}.weights", basename));
+        let num_columns = self.columns.len();
+        let mut writer = ArcValuesWriter::new(&format!("{}.weights", basename), num_nodes)?;
+        let mut chunks = self
+            .chunks
+            .iter()
+            .map(|path| {
+                Ok(Chunk {
+                    file: io::BufReader::new(fs::File::open(path)?),
+                    record: vec![0; 8 + 2 * num_columns],
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // the current weights of each chunk
+        let mut weights = vec![vec![0; num_columns]; chunks.len()];
+        let mut heap = BinaryHeap::new();
+        for (chunk_id, chunk) in chunks.iter_mut().enumerate() {
+            if let Some(arc) = chunk.next(&mut weights[chunk_id])? {
+                heap.push(Reverse((arc, chunk_id)));
+            }
+        }
+        let mut current: Option<((usize, usize), Vec<usize>)> = None;
+        while let Some(Reverse((arc, chunk_id))) = heap.pop() {
+            match &mut current {
+                Some((current_arc, current_weights)) if *current_arc == arc => {
+                    for (current, weight) in current_weights.iter_mut().zip(&weights[chunk_id]) {
+                        *current = (*current).max(*weight as usize);
+                    }
+                }
+                _ => {
+                    if let Some(((src, dst), current_weights)) = current.take() {
+                        writer.push(src, dst, &current_weights)?;
+                    }
+                    let arc_weights = weights[chunk_id].iter().map(|&w| w as usize).collect();
+                    current = Some((arc, arc_weights));
+                    pl.light_update();
+                }
+            }
+            if let Some(next) = chunks[chunk_id].next(&mut weights[chunk_id])? {
+                heap.push(Reverse((next, chunk_id)));
+            }
+        }
+        if let Some(((src, dst), current_weights)) = current {
+            writer.push(src, dst, &current_weights)?;
+        }
+        let num_arcs = writer.finish()?;
+        fs::remove_dir_all(&self.dir)?;
+        for dir in &self.other_dirs {
+            fs::remove_dir_all(dir)?;
+        }
+        pl.done();
+
+        let metadata = WeightsMetadata {
+            num_nodes,
+            num_arcs,
+            columns: self.columns,
+        };
+        serde_json::to_writer_pretty(
+            fs::File::create(format!("{}.weights.json", basename))?,
+            &metadata,
+        )?;
+        Ok(metadata)
+    }
+}
+
+/// Read-only, memory-mapped access to the weights of the arcs of a source.
+pub struct ArcWeights {
+    metadata: WeightsMetadata,
+    values: ArcValues,
+}
+
+impl ArcWeights {
+    /// Load the weights with the given basename, e.g. `res.string_links`.
+    pub fn load<P: AsRef<Path>>(basename: P) -> Result<Self> {
+        let basename = basename.as_ref().display();
+        let path = format!("{}.weights.json", basename);
+        let metadata: WeightsMetadata = serde_json::from_reader(io::BufReader::new(
+            fs::File::open(&path).with_context(|| format!("Could not open {}", path))?,
+        ))
+        .with_context(|| format!("Could not parse {}", path))?;
+        let values = ArcValues::load(&format!("{}.weights", basename), metadata.num_nodes)?;
+        Ok(Self { metadata, values })
+    }
+
+    /// The metadata of the weights.
+    pub fn metadata(&self) -> &WeightsMetadata {
+        &self.metadata
+    }
+
+    /// The index of the weight with the given name.
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.metadata
+            .columns
+            .iter()
+            .position(|column| column == name)
+    }
+
+    /// The weights of the arc from `src` to `dst`, if it has any.
+    pub fn weights(&self, src: usize, dst: usize) -> Option<Vec<usize>> {
+        self.values.values(src, dst)
+    }
+
+    /// The weighted successors of a node in increasing order, each with its
+    /// weights.
+    pub fn weighted_successors(&self, node: usize) -> Successors<'_> {
+        self.values.successors(node)
+    }
+
+    /// The successors of a node whose weight in `column` is at least `min`,
+    /// see [`ArcWeights::column`].
+    pub fn successors_at_least(
+        &self,
+        node: usize,
+        column: usize,
+        min: usize,
+    ) -> Result<impl Iterator<Item = usize> + '_> {
+        ensure!(
+            column < self.metadata.columns.len(),
+            "No weight column {}, there are {}",
+            column,
+            self.metadata.columns.len()
+        );
+        Ok(self
+            .weighted_successors(node)
+            .filter(move |(_, weights)| weights[column] >= min)
+            .map(|(succ, _)| succ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let dir = temp_dir(std::env::temp_dir());
+        let columns = vec!["combined_score".to_string(), "experiments".to_string()];
+        // tiny batches, so that the duplicates are in different chunks
+        let mut weights = WeightsBuilder::new(&dir, 2, columns.clone());
+        weights.push(0, 2, &[700, 0])?;
+        weights.push(1, 0, &[150, 150])?;
+        weights.push(0, 1, &[400, 300])?;
+        assert!(weights.push(0, 1, &[400]).is_err());
+        let mut other = WeightsBuilder::new(&dir, 2, columns.clone());
+        // the largest weight of each column is kept
+        other.push(0, 2, &[500, 100])?;
+        other.push(0, 2, &[600, 50])?;
+        other.push(2, 1, &[999, 999])?;
+        weights.append(other)?;
+        let basename = Path::new(&dir).join("res.string_links");
+        let metadata = weights.build(&basename, 3)?;
+        assert_eq!(metadata.num_arcs, 4);
+
+        let weights = ArcWeights::load(&basename)?;
+        assert_eq!(weights.metadata().columns, columns);
+        assert_eq!(weights.weights(0, 2), Some(vec![700, 100]));
+        assert_eq!(weights.weights(0, 1), Some(vec![400, 300]));
+        assert_eq!(weights.weights(2, 1), Some(vec![999, 999]));
+        assert_eq!(weights.weights(2, 0), None);
+        let experiments = weights.column("experiments").unwrap();
+        assert_eq!(
+            weights
+                .successors_at_least(0, experiments, 100)?
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(
+            weights
+                .successors_at_least(0, experiments, 101)?
+                .collect::<Vec<_>>(),
+            [1]
+        );
+        assert_eq!(weights.successors_at_least(1, 0, 151)?.count(), 0);
+        assert!(weights.successors_at_least(0, 2, 0).is_err());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_other_columns() {
+        let dir = temp_dir(std::env::temp_dir());
+        let mut weights = WeightsBuilder::new(&dir, 2, vec!["combined_score".to_string()]);
+        let other = WeightsBuilder::new(&dir, 2, vec!["experiments".to_string()]);
+        assert!(weights.append(other).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}